@@ -6,12 +6,20 @@ use crossterm::{
     cursor::MoveUp,
     execute,
 };
-use math::{polynomial::Polynomial, exponential::Exponential};
+use math::{
+    polynomial::Polynomial,
+    exponential::Exponential,
+    quadratic::Quadratic,
+    radical::Radical,
+    recurrence::Recurrence,
+    model::Model,
+    function::Expression,
+    predict::Predict,
+};
 use config::{fmt::{FmtEnum, formatters}, output::Output};
 use indexmap::map::IndexMap;
 use menu::Menu;
-use util::parse;
-use num::rational::Ratio;
+use num::BigRational;
 
 fn main() {
     println!("Setup:");
@@ -31,9 +39,16 @@ fn main() {
     output_map.insert(String::from("Console"), Output::Console);
     output_map.insert(String::from("File"), Output::File(None));
     let mut output_menu = Menu::new(String::from("Output location"), output_map);
+
+    let mut predictions_map = IndexMap::new();
+    for &count in [5, 10, 20, 0].iter() {
+        predictions_map.insert(if count == 0 { String::from("None") } else { count.to_string() }, count);
+    }
+    let mut predictions_menu = Menu::new(String::from("Predicted values"), predictions_map);
     
     let default_fmt = fmt_menu.get_user_input();
     let mut default_output = output_menu.get_user_input();
+    let prediction_count = *predictions_menu.get_user_input();
     let default_output_owned;
     if let Output::File(None) = default_output {
         let (path, lines) = Output::file_name_ui();
@@ -48,43 +63,139 @@ fn main() {
 
     execute!(
         stdout(),
-        MoveUp(4),
+        MoveUp(5),
         Clear(ClearType::FromCursorDown),
     ).unwrap();
     
+    println!(r#"Enter values separated by commas. Start with "sum" to add them up, or "gf" for their generating function."#);
     loop {
-        let pattern = get_pattern();
-        match Exponential::from_values(&pattern) {
-            Some(exponential) => default_output.print(&default_fmt.format(&exponential)),
-            None => match Polynomial::from_values(&pattern, 0) {
-                Some(polynomial) => default_output.print(&default_fmt.format(&polynomial)),
-                None => match Exponential::from_values(&pattern) {
-                    Some(exponential) => default_output.print(&default_fmt.format(&exponential)),
-                    None => println!("No pattern found"),
-                },
+        let (command, values) = get_command();
+        let pattern: Vec<BigRational> = match values.iter().map(Quadratic::to_ratio).collect() {
+            Some(pattern) => pattern,
+            // Only a radical can have irrational values. Its predictions aren't printed, since
+            // they would be irrational too.
+            None => {
+                match command {
+                    Command::Sum => println!("No partial sum found: only polynomials and exponentials can be summed"),
+                    Command::GeneratingFunction => println!("No generating function found: the values aren't rational"),
+                    Command::Pattern => match Radical::from_values(&values, 0) {
+                        Ok(radical) => default_output.print(&default_fmt.format(&radical)),
+                        Err(err) => println!("No pattern found: {}", err),
+                    },
+                }
+                continue;
+            }
+        };
+        // The next values after the pattern, formatted like the formula
+        let print_predictions = |model: &dyn Predict| {
+            let predictions: Vec<String> = model.predictions(pattern.len() as i32).take(prediction_count)
+                .map(|value| default_fmt.format(&Expression::Val(value)))
+                .collect();
+            if !predictions.is_empty() {
+                default_output.print(&format!("Next: {}", predictions.join(", ")));
+            }
+        };
+        // S(x) = f(0)+f(1)+...+f(x), where f is the pattern
+        if let Command::Sum = command {
+            if let Ok(exponential) = Exponential::from_values(&pattern) {
+                let partial_sum = exponential.partial_sum();
+                default_output.print(&default_fmt.format(&partial_sum));
+                print_predictions(&partial_sum);
+            }
+            else if let Some(polynomial) = Polynomial::from_values(&pattern, 0).ok().filter(|p| (p.degree() as usize) + 1 < pattern.len()) {
+                match polynomial.partial_sum() {
+                    Some(partial_sum) => {
+                        default_output.print(&default_fmt.format(&partial_sum));
+                        print_predictions(&partial_sum);
+                    },
+                    None => println!("No partial sum found: the degree is too large"),
+                }
+            }
+            else {
+                println!("No partial sum found: only polynomials and exponentials can be summed");
+            }
+            continue;
+        }
+        // a(0)+a(1)x+a(2)x^2+..., which is only a rational function for sequences that follow
+        // a linear recurrence
+        if let Command::GeneratingFunction = command {
+            if let Ok(exponential) = Exponential::from_values(&pattern) {
+                default_output.print(&default_fmt.format(&exponential.generating_function()));
+            }
+            else if let Some(polynomial) = Polynomial::from_values(&pattern, 0).ok().filter(|p| (p.degree() as usize) + 1 < pattern.len()) {
+                match polynomial.generating_function() {
+                    Some(generating_function) => default_output.print(&default_fmt.format(&generating_function)),
+                    None => println!("No generating function found: the degree is too large"),
+                }
             }
+            else if let Ok(recurrence) = Recurrence::from_values(&pattern) {
+                match recurrence.generating_function() {
+                    Ok(generating_function) => default_output.print(&default_fmt.format(&generating_function)),
+                    Err(_) => println!("No generating function found: the degree is too large"),
+                }
+            }
+            else {
+                println!("No generating function found: the values don't follow a linear recurrence");
+            }
+            continue;
+        }
+        // The families are tried in the same order as Function::from_values, but recurrences
+        // and piecewise functions are found too
+        match Model::from_values(&pattern, 0) {
+            Ok(model) => {
+                default_output.print(&default_fmt.format(&model));
+                print_predictions(&model);
+            },
+            Err(err) => println!("No pattern found: {}", err),
         }
-        
     }
 }
 
-pub fn get_pattern() -> Vec<Ratio<i32>> {
-    let mut pattern = String::new();
+// What to do with the values that the user entered
+pub enum Command {
+    // Find the pattern that the values follow
+    Pattern,
+    // Find the sum of the first x+1 values of the pattern
+    Sum,
+    // Find the ordinary generating function of the pattern
+    GeneratingFunction,
+}
+
+pub fn get_command() -> (Command, Vec<Quadratic>) {
+    let mut line = String::new();
     print!("Pattern: ");
     stdout().flush().expect("Unable to flush buffer");
     stdin()
-        .read_line(&mut pattern)
+        .read_line(&mut line)
         .expect("Could not read user input");
-    let parsed = pattern.split(',').map(parse);
-    let mut vec: Vec<Ratio<i32>> = Vec::new();
+    let line = line.trim_start();
+    let (command, values) = if let Some(values) = line.strip_prefix("sum") {
+        (Command::Sum, values)
+    }
+    else if let Some(values) = line.strip_prefix("gf") {
+        (Command::GeneratingFunction, values)
+    }
+    else {
+        (Command::Pattern, line)
+    };
+    match parse_values(values) {
+        Some(values) => (command, values),
+        None => get_command(),
+    }
+}
+
+// Prints the error and returns None if any of the values can't be parsed
+fn parse_values(pattern: &str) -> Option<Vec<Quadratic>> {
+    let parsed = pattern.split(',').map(str::parse);
+    let mut vec: Vec<Quadratic> = Vec::new();
     for p in parsed {
         match p {
-            Ok(ratio) => vec.push(ratio),
+            Ok(value) => vec.push(value),
             Err(err) => {
                 println!("Error: {}", err);
-                return get_pattern();
+                return None;
             }
         }
     }
-    vec
+    Some(vec)
 }
\ No newline at end of file
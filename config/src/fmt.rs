@@ -27,6 +27,44 @@ pub fn superscript(num: i32) -> String {
     s
 }
 
+fn superscript_char(c: char, variable: char) -> Option<char> {
+    match c {
+        '0'..='9' => superscript(c.to_digit(10).unwrap() as i32).chars().next(),
+        '-' => Some('⁻'),
+        '+' => Some('⁺'),
+        '(' => Some('⁽'),
+        ')' => Some('⁾'),
+        '/' => Some('ᐟ'),
+        _ if c == variable || c == 'x' => Some('ˣ'),
+        _ => None,
+    }
+}
+
+pub fn subscript(num: u32) -> String {
+    num.to_string().chars().map(|c| char::from_u32(0x2080 + c.to_digit(10).unwrap()).unwrap()).collect()
+}
+
+// Wraps s in parentheses if it contains an operator that isn't a leading negative sign
+fn group(s: &str) -> String {
+    if s.chars().skip(1).any(|c| "+-*/·".contains(c)) {
+        format!("({})", s)
+    }
+    else {
+        s.to_owned()
+    }
+}
+
+// Wraps s in parentheses unless it is a single character or a number, so that a postfix
+// operator such as ! or a prefix operator such as √ applies to all of it
+fn unary_group(s: &str) -> String {
+    if s.chars().count() == 1 || s.chars().all(|c| c.is_ascii_digit()) {
+        s.to_owned()
+    }
+    else {
+        format!("({})", s)
+    }
+}
+
 pub trait FmtAble {
     fn format(&self, f: &impl FmtEr) -> String;
 }
@@ -49,7 +87,10 @@ pub trait FmtEr {
         }
     }
     fn multiply(&self, a: &str, b: &str) -> String {
-        if a.ends_with(self.x()) || b.starts_with(self.x()) || a.ends_with(')') && b.starts_with('(') {
+        if a.ends_with(char::is_alphabetic) && b.starts_with(char::is_alphabetic) || b.starts_with(char::is_numeric) {
+            format!("{}*{}", a, b)
+        }
+        else if a.ends_with(self.x()) || b.starts_with(self.x()) || a.ends_with(')') && b.starts_with('(') {
             format!("{}{}", a, b)
         }
         else {
@@ -68,8 +109,21 @@ pub trait FmtEr {
             }
         }
     }
+    // Like divide, but a and b may be sums or products
+    fn fraction(&self, a: &str, b: &str) -> String {
+        // 1/2x could be read as x/2, so the denominator also needs parentheses if it is
+        // a product without an operator
+        let implicit_product = b.chars().zip(b.chars().skip(1))
+            .any(|pair| (pair.0.is_ascii_digit() || pair.0 == ')') && (pair.1.is_alphabetic() || pair.1 == '('));
+        self.divide(group(a).as_str(), if implicit_product { format!("({})", b) } else { group(b) }.as_str())
+    }
     fn pow(&self, a: &str, b: &str) -> String {
-        format!("{}^{}", a, b)
+        if b.starts_with('-') {
+            format!("{}^({})", a, b)
+        }
+        else {
+            format!("{}^{}", a, group(b))
+        }
     }
     fn neg(&self, a: &str) -> String {
         if a.starts_with('-') {
@@ -79,9 +133,73 @@ pub trait FmtEr {
             String::from("-") + a
         }
     }
+    fn abs(&self, a: &str) -> String {
+        format!("|{}|", a)
+    }
+    fn floor(&self, a: &str) -> String {
+        format!("floor({})", a)
+    }
+    fn ceil(&self, a: &str) -> String {
+        format!("ceil({})", a)
+    }
+    fn factorial(&self, a: &str) -> String {
+        format!("{}!", unary_group(a))
+    }
+    fn double_factorial(&self, a: &str) -> String {
+        format!("{}!!", unary_group(a))
+    }
+    // n choose k
+    fn binomial(&self, n: &str, k: &str) -> String {
+        format!("C({}, {})", n, k)
+    }
+    fn sqrt(&self, a: &str) -> String {
+        format!("sqrt({})", a)
+    }
+    // The base b logarithm of a
+    fn log(&self, b: &str, a: &str) -> String {
+        format!("log_{}({})", group(b), a)
+    }
+    // The natural logarithm of a
+    fn ln(&self, a: &str) -> String {
+        format!("ln({})", a)
+    }
+    fn sin(&self, a: &str) -> String {
+        format!("sin({})", a)
+    }
+    fn cos(&self, a: &str) -> String {
+        format!("cos({})", a)
+    }
+    fn pi(&self) -> String {
+        String::from("pi")
+    }
     fn x(&self) -> char {
         'x'
     }
+    // The condition that x is within the given bounds, where each bound is (value, inclusive)
+    fn between(&self, lower: Option<(&str, bool)>, upper: Option<(&str, bool)>) -> String {
+        let mut s = self.x().to_string();
+        if let Some(lower) = lower {
+            s = format!("{}{}{}", lower.0, self.less_than(lower.1), s);
+        }
+        if let Some(upper) = upper {
+            s = format!("{}{}{}", s, self.less_than(upper.1), upper.0);
+        }
+        s
+    }
+    fn less_than(&self, or_equal: bool) -> String {
+        String::from(if or_equal { "<=" } else { "<" })
+    }
+    // The condition that a and b have the same remainder when divided by m
+    fn congruent(&self, a: &str, b: &str, m: &str) -> String {
+        format!("{} mod {} = {}", a, m, b)
+    }
+    // Each case is (value, condition)
+    fn cases(&self, cases: &[(String, String)]) -> String {
+        format!("{{{}}}", cases.iter().map(|case| format!("{} if {}", case.0, case.1)).collect::<Vec<_>>().join(", "))
+    }
+    fn sequence_term(&self, index: &str) -> String {
+        format!("a({})", index)
+    }
 }
 
 pub mod formatters {
@@ -95,24 +213,47 @@ pub mod formatters {
     pub struct Unicode;
     impl FmtEr for Unicode {
         fn multiply(&self, a: &str, b: &str) -> String {
-            if a.ends_with(self.x()) || b.starts_with(self.x()) || a.ends_with(')') || b.starts_with('(') {
+            if !b.starts_with(char::is_numeric) && (a.ends_with(self.x()) || b.starts_with(self.x()) || a.ends_with(')') || b.starts_with('(')) {
                 format!("{}{}", a, b)
             }
             else {
                 format!("{}·{}", a, b)
             }
         }
+        // The exponent is written in superscripts if every character has one
         fn pow(&self, a: &str, b: &str) -> String {
-            if b == self.x().to_string() {
-                format!("{}ˣ", a)
+            match b.chars().map(|c| superscript_char(c, self.x())).collect::<Option<String>>() {
+                Some(exponent) => format!("{}{}", a, exponent),
+                None => format!("{}^({})", a, b),
             }
-            else {
-                format!("{}{}", a, superscript(b.parse().unwrap()))
+        }
+        fn sqrt(&self, a: &str) -> String {
+            format!("√{}", unary_group(a))
+        }
+        fn log(&self, b: &str, a: &str) -> String {
+            match b.parse() {
+                Ok(b) => format!("log{}({})", subscript(b), a),
+                Err(_) => format!("log_{}({})", group(b), a),
             }
         }
+        fn pi(&self) -> String {
+            String::from("π")
+        }
         fn x(&self) -> char {
             x
         }
+        fn floor(&self, a: &str) -> String {
+            format!("⌊{}⌋", a)
+        }
+        fn ceil(&self, a: &str) -> String {
+            format!("⌈{}⌉", a)
+        }
+        fn less_than(&self, or_equal: bool) -> String {
+            String::from(if or_equal { "≤" } else { "<" })
+        }
+        fn congruent(&self, a: &str, b: &str, m: &str) -> String {
+            format!("{}≡{} (mod {})", a, b, m)
+        }
     }
 
     #[derive(Debug)]
@@ -125,15 +266,106 @@ pub mod formatters {
         fn pow(&self, a: &str, b: &str) -> String {
             format!("Math.pow({}, {})", a, b)
         }
+        // Java drops the remainder when dividing two ints, so one side is made a double,
+        // unless it already is one because it comes from a Math function
+        fn divide(&self, a: &str, b: &str) -> String {
+            let is_integer = |s: &str| {
+                let digits = s.strip_prefix('-').unwrap_or(s);
+                !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())
+            };
+            match b {
+                _ if a == "0" => a.to_owned(),
+                "1" => a.to_owned(),
+                "-1" => self.neg(a),
+                _ if is_integer(a) => format!("{}.0/{}", a, b),
+                _ if is_integer(b) => format!("{}/{}.0", a, b),
+                _ if a.contains("Math.") || b.contains("Math.") => format!("{}/{}", a, b),
+                _ => format!("1.0*{}/{}", a, b),
+            }
+        }
+        fn abs(&self, a: &str) -> String {
+            format!("Math.abs({})", a)
+        }
+        fn floor(&self, a: &str) -> String {
+            format!("Math.floor({})", a)
+        }
+        fn ceil(&self, a: &str) -> String {
+            format!("Math.ceil({})", a)
+        }
+        // Java and JS don't have these built in, so they must be defined by the user
+        fn factorial(&self, a: &str) -> String {
+            format!("factorial({})", a)
+        }
+        fn double_factorial(&self, a: &str) -> String {
+            format!("doubleFactorial({})", a)
+        }
+        fn binomial(&self, n: &str, k: &str) -> String {
+            format!("binomial({}, {})", n, k)
+        }
+        fn sqrt(&self, a: &str) -> String {
+            format!("Math.sqrt({})", a)
+        }
+        fn log(&self, b: &str, a: &str) -> String {
+            if b == "10" {
+                format!("Math.log10({})", a)
+            }
+            else {
+                format!("Math.log({})/Math.log({})", a, b)
+            }
+        }
+        fn ln(&self, a: &str) -> String {
+            format!("Math.log({})", a)
+        }
+        fn sin(&self, a: &str) -> String {
+            format!("Math.sin({})", a)
+        }
+        fn cos(&self, a: &str) -> String {
+            format!("Math.cos({})", a)
+        }
+        fn pi(&self) -> String {
+            String::from("Math.PI")
+        }
+        fn between(&self, lower: Option<(&str, bool)>, upper: Option<(&str, bool)>) -> String {
+            let mut conditions = Vec::new();
+            if let Some(lower) = lower {
+                conditions.push(format!("{}{}{}", self.x(), if lower.1 { ">=" } else { ">" }, lower.0));
+            }
+            if let Some(upper) = upper {
+                conditions.push(format!("{}{}{}", self.x(), self.less_than(upper.1), upper.0));
+            }
+            conditions.join(" && ")
+        }
+        fn congruent(&self, a: &str, b: &str, m: &str) -> String {
+            format!("Math.floorMod({}, {}) == {}", a, m, b)
+        }
+        fn cases(&self, cases: &[(String, String)]) -> String {
+            let mut s = cases.last().unwrap().0.clone();
+            for case in cases.iter().rev().skip(1) {
+                s = format!("{} ? {} : {}", case.1, case.0, s);
+            }
+            s
+        }
+        fn sequence_term(&self, index: &str) -> String {
+            format!("a[{}]", index)
+        }
     }
 
     #[derive(Debug)]
     pub struct LaTeX;
     impl FmtEr for LaTeX {
         fn multiply(&self, a: &str, b: &str) -> String {
-            if a.ends_with(self.x()) || b.starts_with(self.x()) || a.ends_with(')') && b.starts_with('(') {
+            if a.ends_with(char::is_alphabetic) && b.starts_with(char::is_alphabetic) {
+                format!("{} {}", a, b)
+            }
+            else if b.starts_with(char::is_numeric) {
+                format!(r"{}\cdot{}", a, b)
+            }
+            else if a.ends_with(self.x()) || b.starts_with(self.x()) || a.ends_with(')') && b.starts_with('(') {
                 format!("{}{}", a, b)
             }
+            else if b.starts_with(char::is_alphabetic) {
+                format!(r"{}\cdot {}", a, b)
+            }
             else {
                 format!(r"{}\cdot{}", a, b)
             }
@@ -150,6 +382,59 @@ pub mod formatters {
                 }
             }
         }
+        fn fraction(&self, a: &str, b: &str) -> String {
+            self.divide(a, b)
+        }
+        fn pow(&self, a: &str, b: &str) -> String {
+            if b.chars().count() > 1 {
+                format!("{}^{{{}}}", a, b)
+            }
+            else {
+                format!("{}^{}", a, b)
+            }
+        }
+        fn abs(&self, a: &str) -> String {
+            format!(r"\left|{}\right|", a)
+        }
+        fn floor(&self, a: &str) -> String {
+            format!(r"\left\lfloor {}\right\rfloor", a)
+        }
+        fn ceil(&self, a: &str) -> String {
+            format!(r"\left\lceil {}\right\rceil", a)
+        }
+        fn binomial(&self, n: &str, k: &str) -> String {
+            format!(r"\binom{{{}}}{{{}}}", n, k)
+        }
+        fn sqrt(&self, a: &str) -> String {
+            format!(r"\sqrt{{{}}}", a)
+        }
+        fn log(&self, b: &str, a: &str) -> String {
+            format!(r"\log_{{{}}}\left({}\right)", b, a)
+        }
+        fn ln(&self, a: &str) -> String {
+            format!(r"\ln\left({}\right)", a)
+        }
+        fn sin(&self, a: &str) -> String {
+            format!(r"\sin\left({}\right)", a)
+        }
+        fn cos(&self, a: &str) -> String {
+            format!(r"\cos\left({}\right)", a)
+        }
+        fn pi(&self) -> String {
+            String::from(r"\pi")
+        }
+        fn less_than(&self, or_equal: bool) -> String {
+            String::from(if or_equal { r"\leq " } else { "<" })
+        }
+        fn congruent(&self, a: &str, b: &str, m: &str) -> String {
+            format!(r"{}\equiv {}\pmod{{{}}}", a, b, m)
+        }
+        fn cases(&self, cases: &[(String, String)]) -> String {
+            format!(r"\begin{{cases}} {} \end{{cases}}", cases.iter().map(|case| format!("{} & {}", case.0, case.1)).collect::<Vec<_>>().join(r" \\ "))
+        }
+        fn sequence_term(&self, index: &str) -> String {
+            format!("a_{{{}}}", index)
+        }
     }
 }
 
@@ -0,0 +1,295 @@
+use core::ops::Bound;
+use num::{BigRational, Signed, One, Zero};
+use config::fmt::{FmtAble, FmtEr};
+use crate::{polynomial::Polynomial, function::{IntervalPart, Expression}, predict::Predict, error::{FitError, EvalError, require, require_unknowns, distinct}, util::{integer, as_points, format_term}};
+use alloc::{vec::Vec, string::String};
+
+// a|x-h|+k
+#[derive(Debug)]
+pub struct AbsoluteValue {
+    stretch: BigRational,
+    vertex: (BigRational, BigRational),
+}
+impl AbsoluteValue {
+    pub fn from_values(values: &Vec<BigRational>, starting: i32) -> Result<Self, FitError> {
+        Self::from_points(&as_points(values, starting))
+    }
+    pub fn from_points(points: &Vec<(BigRational, BigRational)>) -> Result<Self, FitError> {
+        require_unknowns(points, 3)?;
+        let segments = segments(points)?;
+        if segments.len() == 1 {
+            return Err(FitError::Singular);
+        }
+        // The first point after the vertex shows the slope of the second segment
+        if segments[0].slope != -&segments[1].slope {
+            return Err(FitError::InconsistentPoint(segments[1].first + 1));
+        }
+        if segments.len() > 2 {
+            return Err(FitError::InconsistentPoint(segments[1].first + segments[1].points));
+        }
+        let h = segments[1].start.clone().unwrap();
+        Ok(Self {
+            stretch: segments[1].slope.clone(),
+            vertex: (h.clone(), segments[0].apply(&h)),
+        })
+    }
+    pub fn vertex(&self) -> &(BigRational, BigRational) {
+        &self.vertex
+    }
+}
+impl From<&AbsoluteValue> for Expression<BigRational> {
+    fn from(abs: &AbsoluteValue) -> Self {
+        abs_sum(&[(abs.stretch.clone(), abs.vertex.0.clone())], &Polynomial::linear(abs.vertex.1.clone(), BigRational::zero()))
+    }
+}
+impl Predict for AbsoluteValue {
+    fn predict(&self, x: i32) -> Result<BigRational, EvalError> {
+        Expression::from(self).eval(&integer(x))
+    }
+}
+impl FmtAble for AbsoluteValue {
+    fn format(&self, f: &impl FmtEr) -> String {
+        format_abs_sum(&[(self.stretch.clone(), self.vertex.0.clone())], &Polynomial::linear(self.vertex.1.clone(), BigRational::zero()), f)
+    }
+}
+
+// A continuous function made of line segments, such as 0, 1, 2, 2, 2, 1, 0
+#[derive(Debug)]
+pub struct PiecewiseLinear {
+    segments: Vec<Segment>,
+}
+impl PiecewiseLinear {
+    pub fn from_values(values: &Vec<BigRational>, starting: i32) -> Result<Self, FitError> {
+        Self::from_points(&as_points(values, starting))
+    }
+    pub fn from_points(points: &Vec<(BigRational, BigRational)>) -> Result<Self, FitError> {
+        let segments = segments(points)?;
+        if segments.len() < 2 {
+            return Err(FitError::Singular);
+        }
+        // Any points can be connected by line segments, so each segment needs a third point
+        // to show that it is really a line
+        match segments.iter().find(|segment| segment.points < 3) {
+            Some(segment) if segment.first + 2 < points.len() => Err(FitError::InconsistentPoint(segment.first + 2)),
+            Some(_) => Err(FitError::TooFewPoints(points.len() + 1)),
+            None => Ok(Self { segments }),
+        }
+    }
+    pub fn breakpoints(&self) -> Vec<BigRational> {
+        self.segments.iter().filter_map(|segment| segment.start.clone()).collect()
+    }
+    pub fn pieces(&self) -> Vec<(IntervalPart<BigRational>, Polynomial)> {
+        self.segments.iter().enumerate().map(|segment| (
+            IntervalPart(
+                match &segment.1.start {
+                    Some(start) => Bound::Included(start.clone()),
+                    None => Bound::Unbounded,
+                },
+                match self.segments.get(segment.0 + 1) {
+                    Some(next) => Bound::Excluded(next.start.clone().unwrap()),
+                    None => Bound::Unbounded,
+                }
+            ),
+            Polynomial::linear(segment.1.intercept.clone(), segment.1.slope.clone())
+        )).collect()
+    }
+    // Any continuous piecewise linear function can be written as a sum of absolute values
+    // c|x-h| (one for each breakpoint h, where c is half the change in slope) plus a line
+    fn abs_terms(&self) -> (Vec<(BigRational, BigRational)>, Polynomial) {
+        let first = self.segments.first().unwrap();
+        let last = self.segments.last().unwrap();
+        let abs_terms: Vec<(BigRational, BigRational)> = self.segments.windows(2)
+            .map(|pair| ((&pair[1].slope - &pair[0].slope) / integer(2), pair[1].start.clone().unwrap()))
+            .collect();
+        let slope = (&first.slope + &last.slope) / integer(2);
+        let h = &abs_terms[0].1;
+        let intercept = abs_terms.iter().fold(first.apply(h) - &slope * h, |sum, term| sum - &term.0 * (h - &term.1).abs());
+        (abs_terms, Polynomial::linear(intercept, slope))
+    }
+}
+impl From<&PiecewiseLinear> for Expression<BigRational> {
+    fn from(piecewise: &PiecewiseLinear) -> Self {
+        let (terms, linear) = piecewise.abs_terms();
+        abs_sum(&terms, &linear)
+    }
+}
+impl Predict for PiecewiseLinear {
+    fn predict(&self, x: i32) -> Result<BigRational, EvalError> {
+        Expression::from(self).eval(&integer(x))
+    }
+}
+impl FmtAble for PiecewiseLinear {
+    fn format(&self, f: &impl FmtEr) -> String {
+        let (terms, linear) = self.abs_terms();
+        format_abs_sum(&terms, &linear, f)
+    }
+}
+
+#[derive(Debug)]
+struct Segment {
+    slope: BigRational,
+    intercept: BigRational,
+    // The x value where this segment meets the previous one
+    start: Option<BigRational>,
+    // The index of the first point on this segment, and the number of points on it
+    first: usize,
+    points: usize,
+}
+impl Segment {
+    fn apply(&self, x: &BigRational) -> BigRational {
+        &self.slope * x + &self.intercept
+    }
+}
+
+// Splits the points into the longest possible line segments, going left to right. Two
+// segments either meet at a point, or at the intersection of their lines if it is
+// between the last point of one and the first point of the next. The indices in errors and
+// segments are in order of x.
+fn segments(points: &Vec<(BigRational, BigRational)>) -> Result<Vec<Segment>, FitError> {
+    require(points, 2)?;
+    distinct(points)?;
+    let mut points = points.clone();
+    points.sort_by(|a, b| a.0.cmp(&b.0));
+    let on_line = |a: &(BigRational, BigRational), b: &(BigRational, BigRational), c: &(BigRational, BigRational)|
+        (&b.1 - &a.1) * (&c.0 - &a.0) == (&c.1 - &a.1) * (&b.0 - &a.0);
+    let mut segments: Vec<Segment> = Vec::new();
+    let mut last = 0;
+    let mut i = 0;
+    loop {
+        let slope = (&points[i + 1].1 - &points[i].1) / (&points[i + 1].0 - &points[i].0);
+        let intercept = &points[i].1 - &slope * &points[i].0;
+        let mut j = i + 1;
+        while j + 1 < points.len() && on_line(&points[i], &points[i + 1], &points[j + 1]) {
+            j += 1;
+        }
+        let start = match segments.last() {
+            None => None,
+            Some(_) if last == i => Some(points[i].0.clone()),
+            Some(previous) => {
+                if previous.slope == slope {
+                    return Err(FitError::InconsistentPoint(i));
+                }
+                let x = (&intercept - &previous.intercept) / (&previous.slope - &slope);
+                if x <= points[last].0 || x >= points[i].0 {
+                    return Err(FitError::InconsistentPoint(i));
+                }
+                Some(x)
+            }
+        };
+        segments.push(Segment { slope, intercept, start, first: i, points: j - i + 1 });
+        if j == points.len() - 1 {
+            break;
+        }
+        last = j;
+        i = if j + 2 >= points.len() || on_line(&points[j], &points[j + 1], &points[j + 2]) { j } else { j + 1 };
+    }
+    Ok(segments)
+}
+
+// c1|x-h1|+c2|x-h2|...+linear
+fn abs_sum(terms: &[(BigRational, BigRational)], linear: &Polynomial) -> Expression<BigRational> {
+    let mut sum = terms.iter().map(|term| Expression::from(&Polynomial::linear(-&term.1, BigRational::one())).abs().times(&term.0));
+    let first = sum.next().unwrap();
+    let sum = sum.fold(first, |sum, term| sum + term);
+    if linear.is_zero() { sum } else { sum + Expression::from(linear) }
+}
+
+// Formats c1|x-h1|+c2|x-h2|...+linear
+fn format_abs_sum(terms: &[(BigRational, BigRational)], linear: &Polynomial, f: &impl FmtEr) -> String {
+    let mut s = String::new();
+    for term in terms {
+        let abs = format_term(&term.0, f.abs(Polynomial::linear(-&term.1, BigRational::one()).format(f).as_str()).as_str(), f);
+        if s.is_empty() { s = abs }
+        else { s = f.add(s.as_str(), abs.as_str()) }
+    }
+    if !linear.is_zero() {
+        s = f.add(s.as_str(), linear.format(f).as_str());
+    }
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AbsoluteValue, PiecewiseLinear};
+    use crate::{util::{as_ratios, ratio}, function::IntervalPart, error::FitError};
+    use config::fmt::{formatters, FmtAble};
+    use core::ops::Bound;
+    use alloc::vec::Vec;
+    const ASCII: formatters::ASCII = formatters::ASCII;
+
+    mod absolute_value {
+        use super::*;
+
+        #[test]
+        fn parent() {
+            assert_eq!(AbsoluteValue::from_values(&as_ratios(vec![2, 1, 0, 1, 2]), -2).unwrap().format(&ASCII), "|x|");
+        }
+
+        #[test]
+        fn translated() {
+            assert_eq!(AbsoluteValue::from_values(&as_ratios(vec![2, 1, 0, 1, 2]), 0).unwrap().format(&ASCII), "|x-2|");
+        }
+
+        #[test]
+        fn reflected() {
+            assert_eq!(AbsoluteValue::from_values(&as_ratios(vec![-1, 0, 1, 0, -1]), 0).unwrap().format(&ASCII), "-|x-2|+1");
+        }
+
+        #[test]
+        fn vertex_between_points() {
+            let abs = AbsoluteValue::from_values(&as_ratios(vec![4, 2, 0, 1, 3, 5]), 0).unwrap();
+            assert_eq!(abs.vertex(), &(ratio(9, 4), ratio(-1, 2)));
+            assert_eq!(abs.format(&ASCII), "2*|x-(9/4)|-(1/2)");
+        }
+
+        #[test]
+        fn not_symmetric() {
+            // The slope after the vertex is 2, but it would need to be 1
+            assert_eq!(AbsoluteValue::from_values(&as_ratios(vec![3, 2, 1, 3, 5]), 0).unwrap_err(), FitError::InconsistentPoint(3));
+        }
+    }
+
+    mod piecewise_linear {
+        use super::*;
+
+        fn plateau() -> PiecewiseLinear {
+            PiecewiseLinear::from_values(&as_ratios(vec![0, 1, 2, 2, 2, 1, 0]), 0).unwrap()
+        }
+
+        #[test]
+        fn two_pieces() {
+            assert_eq!(PiecewiseLinear::from_values(&as_ratios(vec![3, 2, 1, 3, 5]), 0).unwrap().format(&ASCII), "(3/2)*|x-2|+(1/2)x");
+        }
+
+        #[test]
+        fn three_pieces() {
+            assert_eq!(plateau().format(&ASCII), "-(1/2)*|x-2|-(1/2)*|x-4|+3");
+        }
+
+        #[test]
+        fn breakpoints() {
+            assert_eq!(plateau().breakpoints(), as_ratios(vec![2, 4]));
+        }
+
+        #[test]
+        fn pieces() {
+            let pieces = plateau().pieces();
+            assert_eq!(pieces.iter().map(|piece| piece.0.clone()).collect::<Vec<_>>(), vec![
+                IntervalPart(Bound::Unbounded, Bound::Excluded(ratio(2, 1))),
+                IntervalPart(Bound::Included(ratio(2, 1)), Bound::Excluded(ratio(4, 1))),
+                IntervalPart(Bound::Included(ratio(4, 1)), Bound::Unbounded),
+            ]);
+            assert_eq!(pieces.iter().map(|piece| piece.1.format(&ASCII)).collect::<Vec<_>>(), vec!["x", "2", "-x+6"]);
+        }
+
+        #[test]
+        fn linear() {
+            assert!(PiecewiseLinear::from_values(&as_ratios(vec![1, 2, 3, 4]), 0).is_err());
+        }
+
+        #[test]
+        fn zigzag() {
+            assert!(PiecewiseLinear::from_values(&as_ratios(vec![0, 1, 0, 1, 0]), 0).is_err());
+        }
+    }
+}
@@ -0,0 +1,225 @@
+use num::{BigRational, Zero, One};
+use config::fmt::{FmtAble, FmtEr};
+use crate::{polynomial::Polynomial, quadratic::Quadratic, recurrence::Recurrence, function::Expression, predict::Predict, error::{FitError, EvalError, check}, linear_algebra::{Matrix, Vector}, util::{integer, format_base, format_term}};
+use alloc::{vec::Vec, string::{String, ToString}};
+
+// A sum of polynomials times exponentials, such as x*2^x+3^x-x^2. These are exactly the
+// sequences that follow a homogeneous linear recurrence with constant coefficients.
+#[derive(Debug)]
+pub struct CFinite {
+    // Each term is p(x)*b^x, sorted by base with the largest first
+    terms: Vec<(Polynomial, BigRational)>,
+    // A pair of terms c*r^x+c'*r'^x, where r and r' are conjugate quadratic irrationals, and
+    // so are the coefficients c and c'. Only the first coefficient and base are stored.
+    conjugates: Option<(Quadratic, Quadratic)>,
+}
+impl CFinite {
+    // Finds the shortest recurrence that the values follow. Each root b of its characteristic
+    // polynomial with multiplicity m gives a term p(x)*b^x where p has degree less than m.
+    // Every root must be rational, except for at most one pair of conjugate roots
+    // (a+-b*sqrt(d))/c, which is what happens in Binet's formula for the Fibonacci numbers.
+    pub fn from_values(values: &Vec<BigRational>) -> Result<Self, FitError> {
+        let recurrence = Recurrence::homogeneous(values)?;
+        let order = recurrence.order();
+        let characteristic = recurrence.characteristic()?;
+        // A root of 0 would only affect the first few values
+        if characteristic.coefficients()[0].is_zero() {
+            return Err(FitError::NoClosedForm);
+        }
+        let (rational_roots, remaining) = characteristic.divide_rational_roots()?;
+        let conjugate = match remaining.degree() {
+            0 => None,
+            2 => {
+                let coefficients = remaining.coefficients();
+                let (c, b, a) = (&coefficients[0], &coefficients[1], &coefficients[2] * integer(2));
+                // A negative discriminant would give complex roots
+                let root = Quadratic::sqrt(&(b * b - &a * c * integer(2))).ok_or(FitError::NoClosedForm)?;
+                root.checked_mul(&Quadratic::from(a.recip())).and_then(|root| root.checked_add(&Quadratic::from(-b / a)))
+            },
+            _ => return Err(FitError::NoClosedForm),
+        };
+        let mut roots: Vec<(BigRational, usize)> = Vec::new();
+        for root in rational_roots.into_iter().rev() {
+            match roots.last_mut() {
+                Some(last) if last.0 == root => last.1 += 1,
+                _ => roots.push((root, 1)),
+            }
+        }
+        // Every value is a linear combination of x^j*b^x, and the first order() values
+        // determine the coefficients. If r^x = A+B*sqrt(d) and the coefficient of r^x is
+        // u+v*sqrt(d), then the pair of conjugate terms adds 2uA+2dvB, which is linear in u and v.
+        let matrix = (0..order as i32).map(|x| {
+            let mut row: Vec<BigRational> = roots.iter().flat_map(|root| (0..root.1 as i32).map(move |j| integer(x).pow(j) * root.0.pow(x))).collect();
+            if let Some(conjugate) = &conjugate {
+                let power = conjugate.pow(x as u32);
+                row.push(power.rational_part() * integer(2));
+                row.push(power.irrational_part() * integer(conjugate.radicand() * 2));
+            }
+            row
+        }).collect();
+        let solution: Vec<BigRational> = Matrix::from_rows(matrix).solve(&Vector::new(values[0..order].to_vec())).ok_or(FitError::Singular)?.into();
+        let mut solution = solution.into_iter();
+        let terms = roots.iter()
+            .map(|root| Ok((Polynomial::from_coefficients(&solution.by_ref().take(root.1).collect::<Vec<_>>())?, root.0.clone())))
+            .collect::<Result<Vec<_>, FitError>>()?
+            .into_iter()
+            .filter(|term| !term.0.is_zero())
+            .collect();
+        let conjugates = conjugate.map(|base| {
+            (Quadratic::with_radicand(solution.next().unwrap(), solution.next().unwrap(), base.radicand().clone()), base)
+        });
+        let new = Self { terms, conjugates };
+        check(values.iter().enumerate().map(|value| new.apply(value.0 as i32).and_then(|v| v.to_ratio()).as_ref() == Some(value.1)))?;
+        Ok(new)
+    }
+    // The bases of the exponential terms, largest first. A base of 1 is the polynomial part.
+    pub fn bases(&self) -> Vec<BigRational> {
+        self.terms.iter().map(|term| term.1.clone()).collect()
+    }
+    // The value at x, which is always rational for x >= 0, even if the bases aren't. The
+    // conjugate terms all have the same radicand, so this is only None if the fit is wrong.
+    fn apply(&self, x: i32) -> Option<Quadratic> {
+        let rational = self.terms.iter().fold(BigRational::zero(), |sum, term| sum + term.0.eval(&integer(x)) * term.1.pow(x));
+        let mut value = Quadratic::from(rational);
+        if let Some((coefficient, base)) = &self.conjugates {
+            value = value
+                .checked_add(&coefficient.checked_mul(&base.pow(x as u32))?)?
+                .checked_add(&coefficient.conjugate().checked_mul(&base.conjugate().pow(x as u32))?)?;
+        }
+        Some(value)
+    }
+}
+// The exponential terms come first, then the conjugate pair, then the polynomial part
+impl From<&CFinite> for Expression<BigRational> {
+    fn from(c_finite: &CFinite) -> Self {
+        let mut terms: Vec<Expression<BigRational>> = Vec::new();
+        let mut polynomial = None;
+        for (p, base) in c_finite.terms.iter() {
+            if base.is_one() {
+                polynomial = Some(Expression::from(p));
+                continue;
+            }
+            let power = Expression::Val(base.clone()).pow(Expression::Independent);
+            terms.push(match p.degree() {
+                0 => power.times(&p.coefficients()[0]),
+                _ => Expression::from(p) * power,
+            });
+        }
+        if let Some((coefficient, base)) = &c_finite.conjugates {
+            for (coefficient, base) in [(coefficient.clone(), base.clone()), (coefficient.conjugate(), base.conjugate())].iter() {
+                let power = Expression::from(base).pow(Expression::Independent);
+                terms.push(match coefficient.to_ratio() {
+                    Some(coefficient) => power.times(&coefficient),
+                    None => Expression::from(coefficient) * power,
+                });
+            }
+        }
+        terms.extend(polynomial);
+        let mut terms = terms.into_iter();
+        let first = terms.next().unwrap_or_else(|| Expression::Val(BigRational::zero()));
+        terms.fold(first, |sum, term| sum + term)
+    }
+}
+// Like a recurrence, the sequence starts at x = 0
+impl Predict for CFinite {
+    fn predict(&self, x: i32) -> Result<BigRational, EvalError> {
+        if x < 0 {
+            return Err(EvalError::Undefined);
+        }
+        self.apply(x).and_then(|v| v.to_ratio()).ok_or(EvalError::Irrational)
+    }
+}
+impl FmtAble for CFinite {
+    fn format(&self, f: &impl FmtEr) -> String {
+        let x = f.x().to_string();
+        let mut formatted: Vec<(Quadratic, String)> = self.terms.iter().map(|term| {
+            let s = if term.1.is_one() {
+                term.0.format(f)
+            }
+            else {
+                term.0.format_times(f.pow(format_base(&term.1, f).as_str(), x.as_str()).as_str(), f)
+            };
+            (Quadratic::from(term.1.clone()), s)
+        }).collect();
+        if let Some((coefficient, base)) = &self.conjugates {
+            for (coefficient, base) in [(coefficient.clone(), base.clone()), (coefficient.conjugate(), base.conjugate())].iter() {
+                let power = f.pow(format!("({})", base.format(f)).as_str(), x.as_str());
+                let s = match coefficient.to_ratio() {
+                    Some(coefficient) => format_term(&coefficient, power.as_str(), f),
+                    None if coefficient.is_negative() => f.neg(f.multiply(format!("({})", (-coefficient.clone()).format(f)).as_str(), power.as_str()).as_str()),
+                    None => f.multiply(format!("({})", coefficient.format(f)).as_str(), power.as_str()),
+                };
+                formatted.push((base.clone(), s));
+            }
+        }
+        // The polynomial part goes last, even though 1 may not be the smallest base
+        let one = Quadratic::from(BigRational::one());
+        formatted.sort_by(|a, b| (b.0 != one).cmp(&(a.0 != one))
+            .then(b.0.partial_cmp(&a.0).unwrap()));
+        let mut s = String::new();
+        for term in formatted {
+            if s.is_empty() { s = term.1 }
+            else { s = f.add(s.as_str(), term.1.as_str()) }
+        }
+        s
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CFinite;
+    use crate::{error::FitError, util::{as_ratios, ratio}};
+    use config::fmt::{formatters, FmtAble};
+    const ASCII: formatters::ASCII = formatters::ASCII;
+
+    fn linear_times_exponential() -> CFinite {
+        CFinite::from_values(&as_ratios(vec![1, 3, 9, 25, 65, 161, 385])).unwrap()
+    }
+
+    #[test]
+    fn two_bases() {
+        assert_eq!(CFinite::from_values(&as_ratios(vec![2, 5, 13, 35, 97])).unwrap().format(&ASCII), "3^x+2^x");
+    }
+
+    #[test]
+    fn repeated_base() {
+        assert_eq!(linear_times_exponential().format(&ASCII), "x*2^x+1");
+    }
+
+    #[test]
+    fn polynomial_coefficient() {
+        assert_eq!(CFinite::from_values(&as_ratios(vec![1, 4, 12, 32, 80])).unwrap().format(&ASCII), "(x+1)*2^x");
+    }
+
+    #[test]
+    fn minus_polynomial() {
+        assert_eq!(CFinite::from_values(&as_ratios(vec![1, 1, 2, 5, 12, 27, 58])).unwrap().format(&ASCII), "2^x-x");
+    }
+
+    #[test]
+    fn fractional_base() {
+        let values = vec![ratio(1, 1), ratio(3, 2), ratio(9, 4), ratio(25, 8), ratio(65, 16), ratio(161, 32), ratio(385, 64)];
+        assert_eq!(CFinite::from_values(&values).unwrap().format(&ASCII), "(1/2)^x+x");
+    }
+
+    #[test]
+    fn bases() {
+        assert_eq!(linear_times_exponential().bases(), as_ratios(vec![2, 1]));
+    }
+
+    #[test]
+    fn binet() {
+        assert_eq!(CFinite::from_values(&as_ratios(vec![0, 1, 1, 2, 3, 5, 8])).unwrap().format(&ASCII), "(sqrt(5)/5)((1+sqrt(5))/2)^x-(sqrt(5)/5)((1-sqrt(5))/2)^x");
+    }
+
+    #[test]
+    fn conjugate_pair_and_rational_base() {
+        // 2^x+(1+sqrt(2))^x+(1-sqrt(2))^x
+        assert_eq!(CFinite::from_values(&as_ratios(vec![3, 4, 10, 22, 50, 114, 262])).unwrap().format(&ASCII), "(1+sqrt(2))^x+2^x+(1-sqrt(2))^x");
+    }
+
+    #[test]
+    fn complex_roots() {
+        assert_eq!(CFinite::from_values(&as_ratios(vec![1, 0, -1, 0, 1, 0, -1])).unwrap_err(), FitError::NoClosedForm);
+    }
+}
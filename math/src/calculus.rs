@@ -0,0 +1,174 @@
+use num::{BigRational, One, Zero, Signed};
+use crate::{function::{Function, Expression::{self, *}}, util::integer};
+use alloc::vec::Vec;
+
+impl Expression<BigRational> {
+    // The simplified derivative, or None if it doesn't exist as an expression. floor(u) and
+    // u! aren't differentiable as functions of x, and b^u isn't if b is a negative constant.
+    pub fn derivative(&self) -> Option<Self> {
+        Some(self.differentiate()?.simplify())
+    }
+
+    fn differentiate(&self) -> Option<Self> {
+        let d = |e: &Self| e.differentiate();
+        let depends = |e: &Self| e.contains(&Independent);
+        Some(match self {
+            Add(a, b) => d(a)? + d(b)?,
+            Sub(a, b) => d(a)? - d(b)?,
+            Mul(a, b) => d(a)? * (**b).clone() + (**a).clone() * d(b)?,
+            Div(a, b) => (d(a)? * (**b).clone() - (**a).clone() * d(b)?) / (**b).clone().pow(integer(2)),
+            Pow(a, b) if !depends(b) => (**b).clone() * (**a).clone().pow((**b).clone() - Val(BigRational::one())) * d(a)?,
+            Pow(a, _) if matches!(a.as_ref(), Val(base) if !base.is_positive()) => return None,
+            Pow(a, b) if !depends(a) => self.clone() * (**a).clone().ln() * d(b)?,
+            Pow(a, b) => self.clone() * (d(b)? * (**a).clone().ln() + (**b).clone() * d(a)? / (**a).clone()),
+            Ln(a) => d(a)? / (**a).clone(),
+            Sin(a) => (**a).clone().cos() * d(a)?,
+            Cos(a) => Val(-BigRational::one()) * (**a).clone().sin() * d(a)?,
+            // The derivative of |u| is u'*u/|u|, which is undefined where u is 0
+            Abs(a) => d(a)? * (**a).clone() / self.clone(),
+            Floor(a) | Factorial(a) if depends(a) => return None,
+            Floor(_) | Factorial(_) | Val(_) | Pi => Val(BigRational::zero()),
+            Independent => Val(BigRational::one()),
+        })
+    }
+
+    // A simplified antiderivative, or None if there isn't a rule for it. Each term of the
+    // simplified expression must be a constant times a power of u, b^u, sin(u), cos(u),
+    // ln(u) or |u|, where u is linear in x. Terms like x*2^x, which would need integration
+    // by parts, aren't supported.
+    pub fn antiderivative(&self) -> Option<Self> {
+        let mut terms = Vec::new();
+        summands(&self.simplify(), false, &mut terms);
+        let antiderivative = terms.iter().try_fold(Val(BigRational::zero()), |sum, term| Some(sum + integrate(term)?))?;
+        Some(antiderivative.simplify())
+    }
+}
+
+impl Function<BigRational> {
+    pub fn derivative(&self) -> Option<Self> {
+        self.expression().derivative().map(Self::from)
+    }
+
+    pub fn antiderivative(&self) -> Option<Self> {
+        self.expression().antiderivative().map(Self::from)
+    }
+}
+
+// Splits a sum into its terms, negating the ones that are subtracted
+fn summands(e: &Expression<BigRational>, negative: bool, terms: &mut Vec<Expression<BigRational>>) {
+    match e {
+        Add(a, b) => {
+            summands(a, negative, terms);
+            summands(b, negative, terms);
+        },
+        Sub(a, b) => {
+            summands(a, negative, terms);
+            summands(b, !negative, terms);
+        },
+        _ if negative => terms.push(Val(-BigRational::one()) * e.clone()),
+        _ => terms.push(e.clone()),
+    }
+}
+
+fn integrate(term: &Expression<BigRational>) -> Option<Expression<BigRational>> {
+    let constant = |e: &Expression<BigRational>| !e.contains(&Independent);
+    if constant(term) {
+        return Some(term.clone() * Independent);
+    }
+    Some(match term {
+        Mul(a, b) if constant(a) => (**a).clone() * integrate(b)?,
+        Mul(a, b) if constant(b) => integrate(a)? * (**b).clone(),
+        Div(a, b) if constant(b) => integrate(a)? / (**b).clone(),
+        // c/u^n is c*u^-n
+        Div(a, b) if constant(a) => (**a).clone() * match b.as_ref() {
+            Pow(u, n) => match n.as_ref() {
+                Val(n) => integrate_power(u, &-n)?,
+                _ => return None,
+            },
+            u => integrate_power(u, &-BigRational::one())?,
+        },
+        Pow(u, n) => match n.as_ref() {
+            Val(n) => integrate_power(u, n)?,
+            // b^u/(u'*ln(b))
+            exponent if constant(u) => term.clone() / (slope(exponent)? * (**u).clone().ln()),
+            _ => return None,
+        },
+        Sin(u) => Val(-BigRational::one()) * (**u).clone().cos() / slope(u)?,
+        Cos(u) => (**u).clone().sin() / slope(u)?,
+        // u*ln(u)-u
+        Ln(u) => ((**u).clone() * (**u).clone().ln() - (**u).clone()) / slope(u)?,
+        // u*|u|/2
+        Abs(u) => (**u).clone() * term.clone() / (Val(integer(2)) * slope(u)?),
+        u => integrate_power(u, &BigRational::one())?,
+    })
+}
+
+// The antiderivative of u^n, where u is linear
+fn integrate_power(u: &Expression<BigRational>, n: &BigRational) -> Option<Expression<BigRational>> {
+    let slope = slope(u)?;
+    let n = n + BigRational::one();
+    Some(if n.is_zero() {
+        u.clone().abs().ln() / slope
+    }
+    else {
+        u.clone().pow(n.clone()) / (Val(n) * slope)
+    })
+}
+
+// The derivative of u, if it is a nonzero constant
+fn slope(u: &Expression<BigRational>) -> Option<Expression<BigRational>> {
+    u.derivative().filter(|slope| !slope.contains(&Independent) && *slope != Val(BigRational::zero()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{function::Expression::{self, *}, util::{integer, ratio}};
+    use num::BigRational;
+
+    fn val(n: i32) -> Expression<BigRational> {
+        Val(integer(n))
+    }
+
+    fn x() -> Expression<BigRational> {
+        Independent
+    }
+
+    #[test]
+    fn derivative() {
+        assert_eq!((x().pow(val(3)) - val(2) * x() + val(5)).derivative(), Some((val(3) * x().pow(val(2)) - val(2)).simplify()));
+        // Product and chain rules
+        assert_eq!((x() * (Pi * x()).sin()).derivative(), Some((Pi * x() * (Pi * x()).cos() + (Pi * x()).sin()).simplify()));
+        assert_eq!((x() + val(1)).ln().derivative(), Some((val(1) / (x() + val(1))).simplify()));
+        assert_eq!(val(2).pow(x()).derivative(), Some((val(2).ln() * val(2).pow(x())).simplify()));
+        assert_eq!(x().pow(x()).derivative().map(|d| d.eval(&integer(1))), Some(Ok(integer(1))));
+    }
+
+    #[test]
+    fn not_differentiable() {
+        assert_eq!(x().floor().derivative(), None);
+        assert_eq!(x().factorial().derivative(), None);
+        assert_eq!(val(-2).pow(x()).derivative(), None);
+    }
+
+    #[test]
+    fn antiderivative() {
+        assert_eq!((val(3) * x().pow(val(2)) + val(1)).antiderivative(), Some((x().pow(val(3)) + x()).simplify()));
+        assert_eq!((val(1) / x()).antiderivative(), Some(x().abs().ln().simplify()));
+        assert_eq!(x().pow(Val(ratio(1, 2))).antiderivative(), Some((Val(ratio(2, 3)) * x().pow(Val(ratio(3, 2)))).simplify()));
+        // ln(2) stays symbolic
+        assert_eq!(val(2).pow(x()).antiderivative(), Some((val(2).pow(x()) / val(2).ln()).simplify()));
+        assert_eq!((Pi * x()).sin().antiderivative(), Some((Val(integer(-1)) * (Pi * x()).cos() / Pi).simplify()));
+    }
+
+    #[test]
+    fn no_rule() {
+        assert_eq!((x() * val(2).pow(x())).antiderivative(), None);
+        assert_eq!(x().pow(val(2)).sin().antiderivative(), None);
+    }
+
+    #[test]
+    fn inverse() {
+        let e = val(3) * x().pow(val(2)) - x().ln() + (val(2) * x()).cos();
+        assert_eq!(e.antiderivative().and_then(|a| a.derivative()), Some(e.simplify()));
+    }
+}
@@ -0,0 +1,122 @@
+use core::fmt::{self, Display, Formatter};
+use alloc::{vec::Vec, string::String};
+
+// The reason that a family of functions doesn't fit some points
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FitError {
+    // At least this many points are needed to be sure of the fit
+    TooFewPoints(usize),
+    // The equations for the unknowns don't have exactly one solution
+    Singular,
+    // A number was too large to be used as an exponent, degree or index
+    Overflow,
+    // The point at this index doesn't fit. If more than one function was tried, it is the
+    // first point that the closest one missed.
+    InconsistentPoint(usize),
+    // Two points have the same x value
+    DuplicateX,
+    // The points follow a pattern, but it can't be written exactly in this form, such as a
+    // recurrence whose characteristic polynomial has complex roots
+    NoClosedForm,
+}
+impl FitError {
+    // Keeps the error from whichever candidate fit the most points
+    pub(crate) fn closest(self, other: Self) -> Self {
+        match (self, other) {
+            (FitError::InconsistentPoint(a), FitError::InconsistentPoint(b)) if b > a => other,
+            (FitError::InconsistentPoint(_), _) => self,
+            _ => other,
+        }
+    }
+}
+impl Display for FitError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            FitError::TooFewPoints(needed) => write!(f, "At least {} points are needed", needed),
+            FitError::Singular => write!(f, "The points don't determine a unique function"),
+            FitError::Overflow => write!(f, "A number is too large"),
+            FitError::InconsistentPoint(index) => write!(f, "Point {} doesn't fit", index),
+            FitError::DuplicateX => write!(f, "Two points have the same x value"),
+            FitError::NoClosedForm => write!(f, "There is no closed form"),
+        }
+    }
+}
+
+// The reason that an expression can't be evaluated exactly at some x
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EvalError {
+    // Dividing by zero, including raising 0 to a negative power
+    DivideByZero,
+    // A number was too large to be used as an exponent, or the result would be too large
+    Overflow,
+    // x is outside of the domain, such as the log of a negative number
+    Undefined,
+    // The value exists, but it isn't rational, such as sqrt(2) or ln(2)
+    Irrational,
+}
+impl Display for EvalError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            EvalError::DivideByZero => write!(f, "Division by zero"),
+            EvalError::Overflow => write!(f, "A number is too large"),
+            EvalError::Undefined => write!(f, "The function is undefined there"),
+            EvalError::Irrational => write!(f, "The value is irrational"),
+        }
+    }
+}
+
+// The reason that a formula can't be read as an expression
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    // The character at this index, counting in chars rather than bytes, wasn't expected
+    Unexpected(usize),
+    // The formula ended in the middle, such as after an operator
+    UnexpectedEnd,
+    // A function or constant that isn't known, or can't be written as an expression, such
+    // as a double factorial
+    Unsupported(String),
+    // A constant is divided by 0, which can't be folded into a value
+    DivideByZero,
+}
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            ParseError::Unexpected(index) => write!(f, "Unexpected character at {}", index),
+            ParseError::UnexpectedEnd => write!(f, "The formula ended unexpectedly"),
+            ParseError::Unsupported(name) => write!(f, "{} isn't supported", name),
+            ParseError::DivideByZero => write!(f, "Division by zero"),
+        }
+    }
+}
+
+// Takes whether each point fits, and returns an error with the index of the first one that doesn't
+pub(crate) fn check(fits: impl IntoIterator<Item = bool>) -> Result<(), FitError> {
+    match fits.into_iter().position(|fits| !fits) {
+        Some(index) => Err(FitError::InconsistentPoint(index)),
+        None => Ok(()),
+    }
+}
+
+// Unwraps each value, or returns an error with the index of the first one that is None
+pub(crate) fn all_some<T>(values: Vec<Option<T>>) -> Result<Vec<T>, FitError> {
+    check(values.iter().map(Option::is_some))?;
+    Ok(values.into_iter().map(Option::unwrap).collect())
+}
+
+// Returns an error if there are fewer than the needed number of points
+pub(crate) fn require<T>(points: &[T], needed: usize) -> Result<(), FitError> {
+    if points.len() < needed { Err(FitError::TooFewPoints(needed)) } else { Ok(()) }
+}
+
+// Returns an error unless there is at least one more point than unknowns, since otherwise
+// almost any points would fit, and the extra point is what shows that the pattern is real
+pub(crate) fn require_unknowns<T>(points: &[T], unknowns: usize) -> Result<(), FitError> {
+    require(points, unknowns + 1)
+}
+
+// Returns an error if two points have the same x value
+pub(crate) fn distinct<X: Ord, Y>(points: &[(X, Y)]) -> Result<(), FitError> {
+    let mut xs: Vec<&X> = points.iter().map(|point| &point.0).collect();
+    xs.sort();
+    if xs.windows(2).any(|pair| pair[0] == pair[1]) { Err(FitError::DuplicateX) } else { Ok(()) }
+}
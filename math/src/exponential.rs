@@ -1,64 +1,205 @@
-use num::rational::Ratio;
+use num::{BigRational, One, Zero, Signed};
 use config::fmt::{FmtEr, FmtAble};
+use crate::{polynomial::Polynomial, rational_fn::{RationalFunction, times_one_minus_x}, function::Expression, predict::Predict, error::{FitError, EvalError, require, check}, util::{integer, format_base, format_term}};
 use alloc::{vec::Vec, string::{String, ToString}, format};
 
+#[derive(Debug)]
 pub struct Exponential {
-    stretch: Ratio<i32>,
-    ratio: Ratio<i32>,
-    asymptote: Ratio<i32>,
+    stretch: BigRational,
+    ratio: BigRational,
+    asymptote: BigRational,
 }
 impl Exponential {
-    pub fn from_values(values: &Vec<Ratio<i32>>) -> Option<Self> {
-        if values.len() < 3 || values[0] == values[1] {
-            None
+    pub fn from_values(values: &Vec<BigRational>) -> Result<Self, FitError> {
+        require(values, 3)?;
+        if values[0] == values[1] {
+            return Err(FitError::Singular);
         }
-        else {
-            let ratio = (values[2] - values[1]) / (values[1] - values[0]);
-            let stretch = (values[1] - values[0]) / (ratio - 1);
-            let asymptote = values[0] - stretch;
-            let new = Self {
-                stretch,
-                ratio,
-                asymptote,
-            };
-            for value in values.iter().enumerate() {
-                if value.1 != &new.apply(&(value.0 as i32)) {
-                    return None;
-                }
-            }
-            Some(new)
+        let ratio = (&values[2] - &values[1]) / (&values[1] - &values[0]);
+        // A ratio of 1 would make the values linear, and a ratio of 0 would only affect the
+        // first value
+        if ratio.is_one() || ratio.is_zero() {
+            return Err(FitError::Singular);
         }
+        let stretch = (&values[1] - &values[0]) / (&ratio - BigRational::one());
+        let asymptote = &values[0] - &stretch;
+        let new = Self {
+            stretch,
+            ratio,
+            asymptote,
+        };
+        check(values.iter().enumerate().map(|value| *value.1 == new.apply(&(value.0 as i32))))?;
+        Ok(new)
     }
-    fn apply(&self, value: &i32) -> Ratio<i32> {
-        self.stretch * self.ratio.pow(*value) + self.asymptote
+    // Moves the graph dx to the right
+    pub(crate) fn shift(&mut self, dx: i32) {
+        self.stretch = &self.stretch * self.ratio.pow(-dx);
+    }
+    // b^x is only rational if x is an integer, or b is a perfect power
+    pub fn eval(&self, x: &BigRational) -> Result<BigRational, EvalError> {
+        Expression::from(self).eval(x)
+    }
+    // stretch*ln(b)*b^x, or None if b is negative, since then b^x is only defined at integers
+    pub fn derivative(&self) -> Option<ScaledExponential> {
+        if !self.ratio.is_positive() {
+            return None;
+        }
+        Some(ScaledExponential::from(self).derivative())
+    }
+    // stretch/ln(b)*b^x+asymptote*x, or None if b is negative
+    pub fn antiderivative(&self) -> Option<ScaledExponential> {
+        if !self.ratio.is_positive() {
+            return None;
+        }
+        ScaledExponential::from(self).antiderivative()
+    }
+    // S(x) = f(0)+f(1)+...+f(x), which is stretch*(b^(x+1)-1)/(b-1)+asymptote*(x+1) by the
+    // formula for a geometric series
+    pub fn partial_sum(&self) -> ScaledExponential {
+        let ratio_minus_one = &self.ratio - BigRational::one();
+        ScaledExponential {
+            stretch: &self.stretch * &self.ratio / &ratio_minus_one,
+            log_power: 0,
+            ratio: self.ratio.clone(),
+            polynomial: Polynomial::linear(&self.asymptote - &self.stretch / ratio_minus_one, self.asymptote.clone()),
+        }
+    }
+    // The ordinary generating function f(0)+f(1)x+f(2)x^2+..., which is
+    // stretch/(1-bx)+asymptote/(1-x)
+    pub fn generating_function(&self) -> RationalFunction {
+        let mut denominator = vec![BigRational::one(), -&self.ratio];
+        if !self.asymptote.is_zero() {
+            times_one_minus_x(&mut denominator);
+        }
+        let values: Vec<BigRational> = (0..denominator.len() as i32 - 1).map(|x| self.apply(&x)).collect();
+        // The degree is at most 2, so it can always be stored
+        RationalFunction::generating_function(denominator, &values).unwrap()
+    }
+    fn apply(&self, value: &i32) -> BigRational {
+        &self.stretch * self.ratio.pow(*value) + &self.asymptote
+    }
+}
+impl From<&Exponential> for Expression<BigRational> {
+    fn from(exponential: &Exponential) -> Self {
+        Expression::Val(exponential.ratio.clone()).pow(Expression::Independent).times(&exponential.stretch).plus(&exponential.asymptote)
+    }
+}
+// b^x is always rational at integers
+impl Predict for Exponential {
+    fn predict(&self, x: i32) -> Result<BigRational, EvalError> {
+        Ok(self.apply(&x))
     }
 }
 impl FmtAble for Exponential {
     fn format(&self, f: &impl FmtEr) -> String {
         let mut s;
-        if self.stretch != num::one() && f.multiply("a", "(b)") == "a(b)" || self.ratio.denom() != &1 { 
+        if !self.stretch.is_one() && f.multiply("a", "(b)") == "a(b)" || !self.ratio.denom().is_one() { 
             s = format!("({})", self.ratio);
         }   
         else { 
             s = self.ratio.to_string();
         }
         s = f.pow(s.as_str(), f.x().to_string().as_str());
-        if self.stretch != num::one() {
+        if !self.stretch.is_one() {
             s = f.multiply(self.stretch.to_string().as_str(), s.as_str());
         }
-        if self.asymptote != num::zero() {
+        if !self.asymptote.is_zero() {
             s = f.add(s.as_str(), self.asymptote.to_string().as_str())
         }
         s
     }
 }
 
+// stretch*ln(b)^n*b^x+p(x), which is the form of every derivative and antiderivative of an
+// exponential. ln(b) is irrational for every rational b except 1, so it is kept as a symbol.
+#[derive(Debug)]
+pub struct ScaledExponential {
+    stretch: BigRational,
+    log_power: i32,
+    ratio: BigRational,
+    polynomial: Polynomial,
+}
+impl ScaledExponential {
+    pub fn derivative(&self) -> Self {
+        Self {
+            stretch: self.stretch.clone(),
+            log_power: self.log_power + 1,
+            ratio: self.ratio.clone(),
+            polynomial: self.polynomial.derivative(),
+        }
+    }
+    // The antiderivative whose polynomial part is 0 at x = 0, or None if the degree of the
+    // polynomial would be too large
+    pub fn antiderivative(&self) -> Option<Self> {
+        Some(Self {
+            stretch: self.stretch.clone(),
+            log_power: self.log_power - 1,
+            ratio: self.ratio.clone(),
+            polynomial: self.polynomial.antiderivative()?,
+        })
+    }
+    // This is irrational unless there are no factors of ln(b)
+    pub fn eval(&self, x: &BigRational) -> Result<BigRational, EvalError> {
+        Expression::from(self).eval(x)
+    }
+}
+impl Predict for ScaledExponential {
+    fn predict(&self, x: i32) -> Result<BigRational, EvalError> {
+        self.eval(&integer(x))
+    }
+}
+impl From<&Exponential> for ScaledExponential {
+    fn from(exponential: &Exponential) -> Self {
+        Self {
+            stretch: exponential.stretch.clone(),
+            log_power: 0,
+            ratio: exponential.ratio.clone(),
+            polynomial: Polynomial::linear(exponential.asymptote.clone(), BigRational::zero()),
+        }
+    }
+}
+impl From<&ScaledExponential> for Expression<BigRational> {
+    fn from(exponential: &ScaledExponential) -> Self {
+        let ln = || Expression::Val(exponential.ratio.clone()).ln();
+        let power = Expression::Val(exponential.ratio.clone()).pow(Expression::Independent);
+        let term = match exponential.log_power {
+            0 => power,
+            1 => ln() * power,
+            n if n > 0 => ln().pow(integer(n)) * power,
+            -1 => power / ln(),
+            n => power / ln().pow(integer(-n)),
+        };
+        let term = term.times(&exponential.stretch);
+        if exponential.polynomial.is_zero() { term } else { term + Expression::from(&exponential.polynomial) }
+    }
+}
+impl FmtAble for ScaledExponential {
+    fn format(&self, f: &impl FmtEr) -> String {
+        let mut ln = f.ln(format_base(&self.ratio, f).as_str());
+        if self.log_power.abs() > 1 {
+            ln = f.pow(ln.as_str(), self.log_power.abs().to_string().as_str());
+        }
+        let mut s = f.pow(format_base(&self.ratio, f).as_str(), f.x().to_string().as_str());
+        if self.log_power > 0 {
+            s = f.multiply(ln.as_str(), s.as_str());
+        }
+        s = format_term(&self.stretch, s.as_str(), f);
+        if self.log_power < 0 {
+            s = f.fraction(s.as_str(), ln.as_str());
+        }
+        if !self.polynomial.is_zero() {
+            s = f.add(s.as_str(), self.polynomial.format(f).as_str());
+        }
+        s
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::Exponential;
-    use crate::util::as_ratios;
+    use super::{Exponential, ScaledExponential};
+    use alloc::string::String;
+    use crate::{error::EvalError, util::{as_ratios, ratio}};
     use config::fmt::{formatters, FmtAble};
-    use num::rational::Ratio;
     const ASCII: formatters::ASCII = formatters::ASCII;
 
     #[test]
@@ -73,7 +214,7 @@ mod tests {
 
     #[test]
     fn decay() {
-        assert_eq!(Exponential::from_values(&vec![num::one(), Ratio::new(1, 2), Ratio::new(1, 4)]).unwrap().format(&ASCII), "(1/2)^x");
+        assert_eq!(Exponential::from_values(&vec![num::one(), ratio(1, 2), ratio(1, 4)]).unwrap().format(&ASCII), "(1/2)^x");
     }
 
     #[test]
@@ -95,4 +236,70 @@ mod tests {
     fn unicode() {
         assert_eq!(Exponential::from_values(&as_ratios(vec![2, 4, 8])).unwrap().format(&formatters::Unicode), "2(2)ˣ");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn eval() {
+        let exponential = Exponential::from_values(&as_ratios(vec![5, 9, 17])).unwrap();
+        assert_eq!(exponential.eval(&ratio(-1, 1)), Ok(ratio(3, 1)));
+        assert_eq!(exponential.eval(&ratio(1, 2)), Err(EvalError::Irrational));
+        let square = Exponential::from_values(&as_ratios(vec![1, 4, 16])).unwrap();
+        assert_eq!(square.eval(&ratio(3, 2)), Ok(ratio(8, 1)));
+    }
+
+    fn all_formatters(exponential: &ScaledExponential) -> [String; 4] {
+        [exponential.format(&ASCII), exponential.format(&formatters::Unicode), exponential.format(&formatters::Java_JS), exponential.format(&formatters::LaTeX)]
+    }
+
+    #[test]
+    fn derivative() {
+        let exponential = Exponential::from_values(&as_ratios(vec![5, 9, 17])).unwrap();
+        assert_eq!(all_formatters(&exponential.derivative().unwrap()), [
+            "4*ln(2)*2^x",
+            "4·ln(2)·2ˣ",
+            "4*Math.log(2)*Math.pow(2, x)",
+            r"4\cdot\ln\left(2\right)\cdot2^x",
+        ]);
+        assert_eq!(exponential.derivative().unwrap().derivative().format(&ASCII), "4*ln(2)^2*2^x");
+        assert_eq!(exponential.derivative().unwrap().eval(&ratio(0, 1)), Err(EvalError::Irrational));
+    }
+
+    #[test]
+    fn antiderivative() {
+        let exponential = Exponential::from_values(&as_ratios(vec![5, 9, 17])).unwrap();
+        assert_eq!(all_formatters(&exponential.antiderivative().unwrap()), [
+            "(4*2^x)/ln(2)+x",
+            "(4·2ˣ)/ln(2)+𝑥",
+            "(4*Math.pow(2, x))/Math.log(2)+x",
+            r"\frac{4\cdot2^x}{\ln\left(2\right)}+x",
+        ]);
+        // ln(2) cancels out
+        assert_eq!(exponential.antiderivative().unwrap().derivative().eval(&ratio(1, 1)), Ok(ratio(9, 1)));
+    }
+
+    #[test]
+    fn partial_sum() {
+        let sum = |values: alloc::vec::Vec<i32>| Exponential::from_values(&as_ratios(values)).unwrap().partial_sum().format(&ASCII);
+        assert_eq!(sum(vec![1, 2, 4]), "2*2^x-1");
+        assert_eq!(sum(vec![5, 9, 17]), "8*2^x+x-3");
+        assert_eq!(Exponential::from_values(&vec![ratio(1, 1), ratio(1, 2), ratio(1, 4)]).unwrap().partial_sum().format(&ASCII), "-(1/2)^x+2");
+        let alternating = Exponential::from_values(&as_ratios(vec![1, -2, 4])).unwrap().partial_sum();
+        assert_eq!(alternating.format(&ASCII), "(2/3)(-2)^x+(1/3)");
+        assert_eq!(alternating.eval(&ratio(3, 1)), Ok(ratio(-5, 1)));
+    }
+
+    #[test]
+    fn generating_function() {
+        let generating_function = |values: alloc::vec::Vec<i32>| Exponential::from_values(&as_ratios(values)).unwrap().generating_function().format(&ASCII);
+        assert_eq!(generating_function(vec![1, 2, 4]), "1/(-2x+1)");
+        assert_eq!(generating_function(vec![2, 3, 5]), "(-3x+2)/(2x^2-3x+1)");
+        assert_eq!(generating_function(vec![1, -3, 9]), "1/(3x+1)");
+        assert_eq!(Exponential::from_values(&vec![ratio(1, 1), ratio(1, 2), ratio(1, 4)]).unwrap().generating_function().format(&formatters::LaTeX), r"\frac{2}{-x+2}");
+    }
+
+    #[test]
+    fn negative_base() {
+        let alternating = Exponential::from_values(&as_ratios(vec![1, -2, 4])).unwrap();
+        assert!(alternating.derivative().is_none());
+        assert!(alternating.antiderivative().is_none());
+    }
+}
@@ -0,0 +1,228 @@
+use num::{BigRational, One, Signed};
+use config::fmt::{FmtAble, FmtEr};
+use crate::{function::{Function, Expression::{self, *}}, util::format_ratio};
+use alloc::{string::{String, ToString}, boxed::Box};
+
+// How tightly an operator binds. An operand is put in parentheses if it binds less tightly
+// than its position needs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Precedence {
+    Sum,
+    // Also negation and fractions, since -a*b and 1/2*b mean the same thing either way
+    Product,
+    Power,
+    // Numbers, variables and functions, which never need parentheses
+    Atom,
+}
+
+impl FmtAble for Expression<BigRational> {
+    fn format(&self, f: &impl FmtEr) -> String {
+        self.format_precedence(f).0
+    }
+}
+
+impl FmtAble for Function<BigRational> {
+    fn format(&self, f: &impl FmtEr) -> String {
+        self.expression().format(f)
+    }
+}
+
+impl Expression<BigRational> {
+    fn format_precedence(&self, f: &impl FmtEr) -> (String, Precedence) {
+        let minus_one = Val(-BigRational::one());
+        match self {
+            Add(a, b) => (f.add(a.format(f).as_str(), b.format(f).as_str()), Precedence::Sum),
+            // a-(b+c) isn't a-b+c, but a+(b+c) is a+b+c
+            Sub(a, b) => (f.subtract(a.format(f).as_str(), b.group(f, Precedence::Product).as_str()), Precedence::Sum),
+            // ceil(u) is written as -floor(-u)
+            Mul(a, b) if **a == minus_one => match b.as_ref() {
+                Floor(u) => match u.as_ref() {
+                    Mul(c, u) if **c == minus_one => (f.ceil(u.format(f).as_str()), Precedence::Atom),
+                    _ => (f.neg(b.format(f).as_str()), Precedence::Product),
+                },
+                _ => (f.neg(b.group(f, Precedence::Product).as_str()), Precedence::Product),
+            },
+            Mul(a, b) => match a.as_ref() {
+                Val(c) if c.is_negative() => {
+                    let positive = Mul(Box::new(Val(-c)), b.clone());
+                    (f.neg(positive.format(f).as_str()), Precedence::Product)
+                },
+                _ => {
+                    // (1/2)x, not 1/2x, which could be read as 1/(2x), but \frac{1}{2}x is clear
+                    let left = if a.is_quotient() && !stacks_fractions(f) { format!("({})", a.format(f)) } else { a.group(f, Precedence::Product) };
+                    let mut right = b.group(f, Precedence::Product);
+                    if right.starts_with('-') {
+                        right = format!("({})", right);
+                    }
+                    (f.multiply(left.as_str(), right.as_str()), Precedence::Product)
+                },
+            },
+            // log_b(u) = ln(u)/ln(b)
+            Div(a, b) => match (a.as_ref(), b.as_ref()) {
+                (Ln(u), Ln(base)) => (f.log(base.format(f).as_str(), u.format(f).as_str()), Precedence::Atom),
+                // A formatter like LaTeX that writes fractions as \frac{a}{b} never needs
+                // parentheses
+                _ if stacks_fractions(f) => (f.divide(a.format(f).as_str(), b.format(f).as_str()), Precedence::Atom),
+                // a/(b*c) isn't a/b*c
+                _ => {
+                    let denominator = b.format_precedence(f);
+                    let denominator = if denominator.1 > Precedence::Product { denominator.0 } else { format!("({})", denominator.0) };
+                    (f.divide(a.group(f, Precedence::Product).as_str(), denominator.as_str()), Precedence::Product)
+                },
+            },
+            Pow(a, b) if **b == Val(BigRational::new(1.into(), 2.into())) => (f.sqrt(a.format(f).as_str()), Precedence::Atom),
+            Pow(a, b) => {
+                // The exponent is grouped by the formatter, since it may be a superscript
+                // or an argument, but the base only needs parentheses if the power is
+                // written with an operator
+                let base = if f.pow("a", "b").starts_with('a') {
+                    let base = a.group(f, Precedence::Atom);
+                    if base.starts_with('-') { format!("({})", base) } else { base }
+                }
+                else {
+                    a.format(f)
+                };
+                // Formatters that write a^b only group exponents with an operator in them, so
+                // 2^(2x) would be written as 2^2x. Powers are right associative, so 2^3^x is
+                // fine.
+                let (exponent, precedence) = b.format_precedence(f);
+                let exponent = if precedence < Precedence::Power && f.pow("a", exponent.as_str()) == format!("a^{}", exponent) {
+                    format!("({})", exponent)
+                }
+                else {
+                    exponent
+                };
+                (f.pow(base.as_str(), exponent.as_str()), Precedence::Power)
+            },
+            Ln(a) => (f.ln(a.format(f).as_str()), Precedence::Atom),
+            Sin(a) => (f.sin(a.format(f).as_str()), Precedence::Atom),
+            Cos(a) => (f.cos(a.format(f).as_str()), Precedence::Atom),
+            Abs(a) => (f.abs(a.format(f).as_str()), Precedence::Atom),
+            Floor(a) => (f.floor(a.format(f).as_str()), Precedence::Atom),
+            Factorial(a) => (f.factorial(a.format(f).as_str()), Precedence::Atom),
+            Val(v) if v.is_integer() && !v.is_negative() => (v.to_string(), Precedence::Atom),
+            Val(v) => (format_ratio(v, f), Precedence::Product),
+            Pi => (f.pi(), Precedence::Atom),
+            Independent => (f.x().to_string(), Precedence::Atom),
+        }
+    }
+
+    // Formats the expression, adding parentheses if it binds less tightly than precedence
+    fn group(&self, f: &impl FmtEr, precedence: Precedence) -> String {
+        let (s, own) = self.format_precedence(f);
+        if own < precedence { format!("({})", s) } else { s }
+    }
+
+    fn is_quotient(&self) -> bool {
+        match self {
+            Div(a, b) => !matches!((a.as_ref(), b.as_ref()), (Ln(_), Ln(_))),
+            Val(v) => !v.is_integer(),
+            _ => false,
+        }
+    }
+}
+
+// Whether the formatter writes fractions like \frac{a}{b}, which never need parentheses
+fn stacks_fractions(f: &impl FmtEr) -> bool {
+    !f.divide("a", "b").contains('/')
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{function::{Function, Expression::{self, *}}, util::{as_ratios, integer, ratio}};
+    use config::fmt::{formatters, FmtAble};
+    use num::BigRational;
+    const ASCII: formatters::ASCII = formatters::ASCII;
+
+    fn val(n: i32) -> Expression<BigRational> {
+        Val(integer(n))
+    }
+
+    fn x() -> Expression<BigRational> {
+        Independent
+    }
+
+    #[test]
+    fn sums() {
+        assert_eq!((x() - val(1) - (x() + val(2))).format(&ASCII), "x-1-(x+2)");
+        assert_eq!((x() + (val(1) - x())).format(&ASCII), "x+1-x");
+        assert_eq!((x() + val(-3)).format(&ASCII), "x-3");
+        assert_eq!((x() - val(2) * x()).format(&ASCII), "x-2x");
+    }
+
+    #[test]
+    fn products() {
+        assert_eq!(((x() + val(1)) * (x() - val(1))).format(&ASCII), "(x+1)(x-1)");
+        assert_eq!((Val(ratio(1, 2)) * x()).format(&ASCII), "(1/2)x");
+        assert_eq!((val(-3) * x().pow(val(2))).format(&ASCII), "-3x^2");
+        assert_eq!((val(-1) * (x() + val(1))).format(&ASCII), "-(x+1)");
+        assert_eq!((x() * val(-2)).format(&ASCII), "x(-2)");
+        assert_eq!((val(2) * x().sin()).format(&ASCII), "2*sin(x)");
+    }
+
+    #[test]
+    fn quotients() {
+        assert_eq!((x() / (val(2) * x())).format(&ASCII), "x/(2x)");
+        assert_eq!(((x() + val(1)) / val(2)).format(&ASCII), "(x+1)/2");
+        assert_eq!((x() * val(2) / val(3)).format(&ASCII), "x*2/3");
+        assert_eq!((x().ln() / val(2).ln()).format(&ASCII), "log_2(x)");
+        assert_eq!(((x() + val(1)) / val(2)).format(&formatters::LaTeX), r"\frac{x+1}{2}");
+        assert_eq!(x().pow(Val(ratio(3, 2))).format(&formatters::Java_JS), "Math.pow(x, 3.0/2)");
+        assert_eq!(((x() + val(1)) / (x() + val(2))).format(&formatters::Java_JS), "1.0*(x+1)/(x+2)");
+        assert_eq!(((val(1) + val(5).pow(Val(ratio(1, 2)))) / val(2)).format(&formatters::Java_JS), "(1+Math.sqrt(5))/2.0");
+    }
+
+    #[test]
+    fn powers() {
+        assert_eq!((x() + val(1)).pow(val(2)).format(&ASCII), "(x+1)^2");
+        assert_eq!(val(-2).pow(x()).format(&ASCII), "(-2)^x");
+        assert_eq!(Val(ratio(1, 2)).pow(x()).format(&ASCII), "(1/2)^x");
+        assert_eq!(val(2).pow(x() - val(1)).format(&ASCII), "2^(x-1)");
+        assert_eq!(val(2).pow(val(3).pow(x())).format(&ASCII), "2^3^x");
+        assert_eq!(x().pow(val(2)).pow(val(3)).format(&ASCII), "(x^2)^3");
+        assert_eq!((val(5).pow(Val(ratio(1, 2))) + x()).format(&ASCII), "sqrt(5)+x");
+    }
+
+    #[test]
+    fn functions() {
+        assert_eq!((val(-1) * (val(-1) * x()).floor()).format(&ASCII), "ceil(x)");
+        assert_eq!((x() + val(1)).factorial().format(&ASCII), "(x+1)!");
+        assert_eq!((Pi * x() / val(2)).sin().format(&ASCII), "sin(pi*x/2)");
+        assert_eq!((x() - val(3)).abs().format(&ASCII), "|x-3|");
+    }
+
+    #[test]
+    fn formatters() {
+        let e = val(3) * x().pow(val(2)) - val(2).pow(x() - val(1)) / val(2).ln();
+        assert_eq!(e.format(&ASCII), "3x^2-2^(x-1)/ln(2)");
+        assert_eq!(e.format(&formatters::Unicode), "3𝑥²-2ˣ⁻¹/ln(2)");
+        assert_eq!(e.format(&formatters::Java_JS), "3*Math.pow(x, 2)-Math.pow(2, x-1)/Math.log(2)");
+        assert_eq!(e.format(&formatters::LaTeX), r"3x^2-\frac{2^{x-1}}{\ln\left(2\right)}");
+    }
+
+    // Every formatter's output parses back to the same function
+    #[test]
+    fn round_trip() {
+        let expressions = vec![
+            val(3) * x().pow(val(2)) - val(2).pow(x() - val(1)) / val(2).ln(),
+            (x() + val(1)) / (x() - val(1)) * Val(ratio(-2, 3)),
+            (x() * Pi / val(3)).cos() + (x() - val(2)).abs().factorial(),
+            val(2).pow(Val(ratio(1, 3))) * (val(-1) * x() / val(2)).floor(),
+            val(2).pow(val(2) * x()),
+        ];
+        for e in expressions {
+            for formatted in [e.format(&ASCII), e.format(&formatters::Unicode), e.format(&formatters::Java_JS), e.format(&formatters::LaTeX)].iter() {
+                let parsed: Function<BigRational> = formatted.parse().unwrap_or_else(|err| panic!("{}: {:?}", formatted, err));
+                assert_eq!(parsed, Function::from(e.clone()), "{}", formatted);
+            }
+        }
+    }
+
+    #[test]
+    fn function() {
+        let function = Function::from_values(&as_ratios(vec![1, 2, 4, 8]), 1).unwrap();
+        assert_eq!(function.format(&ASCII), "(1/2)*2^x");
+        assert_eq!(function.format(&formatters::LaTeX), r"\frac{1}{2}\cdot2^x");
+        assert_eq!(Function::from_values(&as_ratios(vec![1, 1, 2, 6, 24]), 0).unwrap().format(&formatters::Unicode), "𝑥!");
+    }
+}
@@ -1,49 +1,92 @@
-use core::ops::{Add, Sub, Mul, Div, Bound};
+use core::{ops::{Add, Sub, Mul, Div, Bound, RangeBounds}, cmp::Ordering};
 use alloc::{
     boxed::Box,
     fmt::Display,
     vec::Vec,
 };
-use num::{rational::Ratio, traits::{NumOps, Pow}};
-use nalgebra::ComplexField;
+use num::{BigRational, One, Zero, Signed, ToPrimitive, rational::Ratio, traits::NumOps};
 use paste::paste;
 use Expression::*;
 use inheriting_wrapper::use_inner;
+use crate::{
+    logarithmic::rational_log,
+    holonomic::factorial_of,
+    sinusoidal::sin_pi,
+    model::Model,
+    error::{FitError, EvalError},
+    util::{integer, as_points, root},
+};
+
+// The largest number of bits that the result of a power or factorial can have
+const MAX_BITS: u64 = 1 << 20;
 
 // Function is simply a wrapper class for Expression
-pub struct Function<T: NumOps + Pow<T, Output = T>> {
+#[derive(Clone, Debug, PartialEq)]
+pub struct Function<T: NumOps> {
     expr: Expression<T>
 }
 
-impl<T: NumOps + Pow<T, Output = T>> Function<T> {
-
+impl<T: NumOps> Function<T> {
+    pub fn expression(&self) -> &Expression<T> {
+        &self.expr
+    }
 }
 
-impl<T: ComplexField + Pow<T, Output = T>> Function<T> {
-    fn from_points(points: &[T]) -> Option<Self> {
-        
+impl Function<BigRational> {
+    pub fn eval(&self, x: &BigRational) -> Result<BigRational, EvalError> {
+        self.expr.eval(x)
+    }
+}
 
-        // Exponential
-        if points.len() >= 3 {
+impl Function<BigRational> {
+    pub fn from_values(values: &Vec<BigRational>, starting: i32) -> Result<Self, FitError> {
+        Self::from_points(&as_points(values, starting))
+    }
+    // The first model that fits and has a formula in terms of x. Recurrences and piecewise
+    // functions aren't functions of x, so they are skipped.
+    pub fn from_points(points: &Vec<(BigRational, BigRational)>) -> Result<Self, FitError> {
+        let start = starting(points).unwrap_or(0);
+        Model::first(points, |model| model.expression(start)).map(Self::from)
+    }
+}
 
-        }
+// The expression is always kept simplified, so equivalent functions are equal
+impl From<Expression<BigRational>> for Function<BigRational> {
+    fn from(expr: Expression<BigRational>) -> Self {
+        Self { expr: expr.simplify() }
+    }
+}
 
-        None
+// The x value of the first point, if the points are at consecutive integers in order
+pub(crate) fn starting(points: &[(BigRational, BigRational)]) -> Option<i32> {
+    let first = &points.first()?.0;
+    if !first.is_integer() || points.iter().enumerate().any(|point| point.1.0 != first + integer(point.0)) {
+        return None;
     }
+    first.to_integer().to_i32()
 }
 
-#[derive(Clone, Debug)]
-enum Expression<T: NumOps + Pow<T, Output = T>> {
+// The order of the variants is the order that simplify() puts factors in
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Expression<T: NumOps> {
+    Pi,
+    Independent,
+    Val(T),
     Add(Box<Expression<T>>, Box<Expression<T>>),
     Sub(Box<Expression<T>>, Box<Expression<T>>),
     Mul(Box<Expression<T>>, Box<Expression<T>>),
     Div(Box<Expression<T>>, Box<Expression<T>>),
     Pow(Box<Expression<T>>, Box<Expression<T>>),
-    Val(T),
-    Independent,
+    // The natural logarithm
+    Ln(Box<Expression<T>>),
+    Sin(Box<Expression<T>>),
+    Cos(Box<Expression<T>>),
+    Abs(Box<Expression<T>>),
+    Floor(Box<Expression<T>>),
+    Factorial(Box<Expression<T>>),
 }
 
-impl<T: NumOps + Pow<T, Output = T>> Function<T> {
+impl<T: NumOps> Function<T> {
     #[use_inner(expr: Expression<T>)]
     const FOO: i32;
 
@@ -71,8 +114,8 @@ fn use_inner_static_fn_test() {
     assert_eq!(Function::<u16>::bar(), 5);
 }
 
-//#[impl_wrapper(Function<T: NumOps + Pow<T, Output = T>>)]
-impl<T: NumOps + Pow<T, Output = T>> Expression<T> {
+//#[impl_wrapper(Function<T: NumOps>)]
+impl<T: NumOps> Expression<T> {
     const FOO: i32 = 2;
 
     fn is_const(&self) -> bool {
@@ -82,7 +125,13 @@ impl<T: NumOps + Pow<T, Output = T>> Expression<T> {
             | Mul(a, b) 
             | Div(a, b) 
             | Pow(a, b) => a.is_const() && b.is_const(),
-            Val(_) => true,
+            Ln(a)
+            | Sin(a)
+            | Cos(a)
+            | Abs(a)
+            | Floor(a)
+            | Factorial(a) => a.is_const(),
+            Val(_) | Pi => true,
             Independent => false,
         }
     }
@@ -90,38 +139,235 @@ impl<T: NumOps + Pow<T, Output = T>> Expression<T> {
     fn bar() -> i32 {
         5
     }
+
+    // Powers aren't evaluated, even if both sides are constants, because the result might
+    // not be a T, such as 2^(1/2)
+    pub fn pow(self, exponent: impl Into<Self>) -> Self {
+        Pow(Box::new(self), Box::new(exponent.into()))
+    }
+
+    pub fn ln(self) -> Self {
+        Ln(Box::new(self))
+    }
+
+    pub fn sin(self) -> Self {
+        Sin(Box::new(self))
+    }
+
+    pub fn cos(self) -> Self {
+        Cos(Box::new(self))
+    }
+
+    pub fn abs(self) -> Self {
+        Abs(Box::new(self))
+    }
+
+    pub fn floor(self) -> Self {
+        Floor(Box::new(self))
+    }
+
+    pub fn factorial(self) -> Self {
+        Factorial(Box::new(self))
+    }
+}
+
+impl<T: NumOps + PartialEq> Expression<T> {
+    // Whether the leaf, such as Pi or Independent, appears anywhere in the expression
+    pub(crate) fn contains(&self, leaf: &Self) -> bool {
+        match self {
+            Add(a, b) | Sub(a, b) | Mul(a, b) | Div(a, b) | Pow(a, b) => a.contains(leaf) || b.contains(leaf),
+            Ln(a) | Sin(a) | Cos(a) | Abs(a) | Floor(a) | Factorial(a) => a.contains(leaf),
+            _ => self == leaf,
+        }
+    }
+}
+
+impl<T: NumOps + Clone> Expression<T> {
+    // Replaces x with another expression
+    pub fn substitute(&self, x: &Self) -> Self {
+        let sub = |e: &Self| Box::new(e.substitute(x));
+        match self {
+            Add(a, b) => Add(sub(a), sub(b)),
+            Sub(a, b) => Sub(sub(a), sub(b)),
+            Mul(a, b) => Mul(sub(a), sub(b)),
+            Div(a, b) => Div(sub(a), sub(b)),
+            Pow(a, b) => Pow(sub(a), sub(b)),
+            Ln(a) => Ln(sub(a)),
+            Sin(a) => Sin(sub(a)),
+            Cos(a) => Cos(sub(a)),
+            Abs(a) => Abs(sub(a)),
+            Floor(a) => Floor(sub(a)),
+            Factorial(a) => Factorial(sub(a)),
+            Val(_) | Pi => self.clone(),
+            Independent => x.clone(),
+        }
+    }
+}
+
+impl Expression<BigRational> {
+    // The exact value at x. Logarithms, sines and fractional powers are usually irrational,
+    // so they are only evaluated when the result is rational.
+    pub fn eval(&self, x: &BigRational) -> Result<BigRational, EvalError> {
+        Ok(match self {
+            Add(a, b) => a.eval(x)? + b.eval(x)?,
+            Sub(a, b) => a.eval(x)? - b.eval(x)?,
+            Mul(a, b) => a.eval(x)? * b.eval(x)?,
+            // log_b(u) = ln(u)/ln(b) can be rational, even though ln(u) and ln(b) aren't
+            Div(a, b) => match (a.as_ref(), b.as_ref()) {
+                (Ln(argument), Ln(base)) => {
+                    let (argument, base) = (argument.eval(x)?, base.eval(x)?);
+                    if !argument.is_positive() || !base.is_positive() {
+                        return Err(EvalError::Undefined);
+                    }
+                    if base.is_one() {
+                        return Err(EvalError::DivideByZero);
+                    }
+                    rational_log(&base, &argument).ok_or(EvalError::Irrational)?
+                },
+                _ => {
+                    let divisor = b.eval(x)?;
+                    if divisor.is_zero() {
+                        return Err(EvalError::DivideByZero);
+                    }
+                    a.eval(x)? / divisor
+                },
+            },
+            Pow(a, b) => power(&a.eval(x)?, &b.eval(x)?)?,
+            // ln(r) is irrational for every rational r except 1
+            Ln(a) => {
+                let argument = a.eval(x)?;
+                if !argument.is_positive() {
+                    return Err(EvalError::Undefined);
+                }
+                if !argument.is_one() {
+                    return Err(EvalError::Irrational);
+                }
+                BigRational::zero()
+            },
+            // sin(r+q*pi) is irrational for every rational r except 0, and for q, by Niven's
+            // theorem, it is only rational if q is a multiple of 1/6. cos(u) = sin(u+pi/2).
+            Sin(a) | Cos(a) => {
+                let (rational, multiple) = a.eval_pi(x)?;
+                if !rational.is_zero() {
+                    return Err(EvalError::Irrational);
+                }
+                let shift = if let Cos(_) = self { integer(1) / integer(2) } else { BigRational::zero() };
+                let sixths = (multiple + shift) * integer(6);
+                if !sixths.is_integer() {
+                    return Err(EvalError::Irrational);
+                }
+                let sixths = (sixths.to_integer() % 12i32).to_i32().unwrap();
+                sin_pi(Ratio::new(sixths, 6)).ok_or(EvalError::Irrational)?
+            },
+            Abs(a) => a.eval(x)?.abs(),
+            Floor(a) => a.eval(x)?.floor(),
+            Factorial(a) => {
+                let n = a.eval(x)?;
+                if !n.is_integer() || n.is_negative() {
+                    return Err(EvalError::Undefined);
+                }
+                let n = n.to_integer().to_i32().ok_or(EvalError::Overflow)?;
+                // n! has fewer than n*log2(n) bits
+                if n as u64 * (32 - n.leading_zeros()) as u64 > MAX_BITS {
+                    return Err(EvalError::Overflow);
+                }
+                factorial_of(n)
+            },
+            Val(v) => v.clone(),
+            Pi => return Err(EvalError::Irrational),
+            Independent => x.clone(),
+        })
+    }
+
+    // Evaluates an expression of the form r+q*pi, such as the argument of a sine, and
+    // returns (r, q)
+    fn eval_pi(&self, x: &BigRational) -> Result<(BigRational, BigRational), EvalError> {
+        Ok(match self {
+            Pi => (BigRational::zero(), BigRational::one()),
+            Add(a, b) => {
+                let (a, b) = (a.eval_pi(x)?, b.eval_pi(x)?);
+                (a.0 + b.0, a.1 + b.1)
+            },
+            Sub(a, b) => {
+                let (a, b) = (a.eval_pi(x)?, b.eval_pi(x)?);
+                (a.0 - b.0, a.1 - b.1)
+            },
+            Mul(a, b) => {
+                let (a, b) = (a.eval_pi(x)?, b.eval_pi(x)?);
+                if a.1.is_zero() { (&a.0 * b.0, a.0 * b.1) }
+                else if b.1.is_zero() { (&a.0 * &b.0, a.1 * b.0) }
+                // A multiple of pi^2
+                else { return Err(EvalError::Irrational) }
+            },
+            Div(a, b) if !b.contains(&Pi) => {
+                let (a, divisor) = (a.eval_pi(x)?, b.eval(x)?);
+                if divisor.is_zero() {
+                    return Err(EvalError::DivideByZero);
+                }
+                (a.0 / &divisor, a.1 / divisor)
+            },
+            _ => (self.eval(x)?, BigRational::zero()),
+        })
+    }
+
+}
+
+// Returns base^exponent if it is rational
+pub(crate) fn power(base: &BigRational, exponent: &BigRational) -> Result<BigRational, EvalError> {
+    if base.is_zero() {
+        return match exponent.cmp(&BigRational::zero()) {
+            Ordering::Greater => Ok(BigRational::zero()),
+            Ordering::Equal => Ok(BigRational::one()),
+            Ordering::Less => Err(EvalError::DivideByZero),
+        };
+    }
+    let numer = exponent.numer().to_i32().ok_or(EvalError::Overflow)?;
+    let denom = exponent.denom().to_u32().ok_or(EvalError::Overflow)?;
+    // The result has about this many bits more than 1
+    let bits = base.numer().bits().max(base.denom().bits()) - 1;
+    if bits * (numer as i64).abs() as u64 / denom as u64 > MAX_BITS {
+        return Err(EvalError::Overflow);
+    }
+    if base.is_negative() && denom % 2 == 0 {
+        return Err(EvalError::Undefined);
+    }
+    Ok(root(base, denom).ok_or(EvalError::Irrational)?.pow(numer))
+}
+
+// Used to build the expressions for the families of functions, leaving out coefficients
+// of 1 and constants of 0 the same way that they are left out when formatting
+impl Expression<BigRational> {
+    pub(crate) fn times(self, coefficient: &BigRational) -> Self {
+        if coefficient.is_one() { self } else { Val(coefficient.clone()) * self }
+    }
+
+    pub(crate) fn plus(self, constant: &BigRational) -> Self {
+        if constant.is_zero() { self } else { self + Val(constant.clone()) }
+    }
 }
 
 macro_rules! impl_ops {
     ($($trait:ident)*) => {
         $(
-            impl<T: NumOps + Pow<T, Output = T>> $trait<Self> for Expression<T> {
+            impl<T: NumOps> $trait<Self> for Expression<T> {
                 type Output = Self;
 
                 paste! {
                     fn [<$trait:snake>](self, o: Self) -> Self {
-                        if self.is_const() && o.is_const() {
-                            if let Val(a) = self {
-                                if let Val(b) = o {
-                                    Val($trait::[<$trait:snake>](a, b))
-                                }
-                                else { unreachable!() }
-                            }
-                            else { unreachable!() }
-                        }
-                        else {
-                            $trait(Box::new(self), Box::new(o))  
+                        match (self, o) {
+                            (Val(a), Val(b)) => Val($trait::[<$trait:snake>](a, b)),
+                            (a, b) => $trait(Box::new(a), Box::new(b)),
                         }
                     }
                 }
             }
 
-            impl<T: NumOps + Pow<T, Output = T>> $trait<T> for Expression<T> {
+            impl<T: NumOps> $trait<T> for Expression<T> {
                 type Output = Self;
 
                 paste! {
                     fn [<$trait:snake>](self, v: T) -> Self {
-                        self + Val(v)
+                        $trait::[<$trait:snake>](self, Val(v))
                     }
                 }
             }
@@ -129,13 +375,131 @@ macro_rules! impl_ops {
     }
 }
 
-impl_ops!(Add Sub Mul Div Pow);
+impl_ops!(Add Sub Mul Div);
 
-impl<T: NumOps + Pow<T, Output = T>> From<T> for Expression<T> {
+impl<T: NumOps> From<T> for Expression<T> {
     fn from(val: T) -> Self {
         Val(val)
     }
 }
 
+#[derive(Clone, Debug, PartialEq)]
 pub struct IntervalPart<T>(pub Bound<T>, pub Bound<T>);
-pub type Interval<T> = Vec<IntervalPart<T>>;
\ No newline at end of file
+
+impl<T> RangeBounds<T> for IntervalPart<T> {
+    fn start_bound(&self) -> Bound<&T> {
+        as_ref(&self.0)
+    }
+
+    fn end_bound(&self) -> Bound<&T> {
+        as_ref(&self.1)
+    }
+}
+
+fn as_ref<T>(bound: &Bound<T>) -> Bound<&T> {
+    match bound {
+        Bound::Included(v) => Bound::Included(v),
+        Bound::Excluded(v) => Bound::Excluded(v),
+        Bound::Unbounded => Bound::Unbounded,
+    }
+}
+
+pub type Interval<T> = Vec<IntervalPart<T>>;
+#[cfg(test)]
+mod tests {
+    use super::{Function, Expression::{self, *}};
+    use crate::{error::{FitError, EvalError}, util::{as_ratios, integer, ratio}};
+
+    fn val(n: i32) -> Expression<num::BigRational> {
+        Val(integer(n))
+    }
+
+    #[test]
+    fn exponential() {
+        assert_eq!(Function::from_values(&as_ratios(vec![1, 2, 4, 8, 16]), 0).unwrap().expression(), &val(2).pow(Independent));
+    }
+
+    #[test]
+    fn shifted() {
+        assert_eq!(Function::from_values(&as_ratios(vec![1, 2, 4, 8]), 1).unwrap().expression(), &(Val(ratio(1, 2)) * val(2).pow(Independent)));
+    }
+
+    #[test]
+    fn polynomial_first() {
+        // x^2 is also c*x^p, but polynomials come first
+        assert_eq!(Function::from_values(&as_ratios(vec![1, 4, 9, 16, 25]), 1).unwrap().expression(), &Independent.pow(val(2)));
+    }
+
+    #[test]
+    fn factorial() {
+        assert_eq!(Function::from_values(&as_ratios(vec![1, 1, 2, 6, 24, 120, 720]), 0).unwrap().expression(), &Independent.factorial());
+    }
+
+    #[test]
+    fn absolute_value() {
+        assert_eq!(Function::from_values(&as_ratios(vec![3, 2, 1, 0, 1, 2, 3]), 0).unwrap().expression(), &(Independent - val(3)).abs());
+    }
+
+    #[test]
+    fn fallback() {
+        // Any two points are on a line, even though there are too few to be sure
+        assert_eq!(Function::from_values(&as_ratios(vec![1, 3]), 0).unwrap().expression(), &(val(2) * Independent + val(1)));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(Function::from_values(&vec![], 0).unwrap_err(), FitError::TooFewPoints(1));
+    }
+
+    // Trying a radical used to factor the huge slope one divisor at a time
+    #[test]
+    fn large_values() {
+        let values = vec![integer(1), integer(num::BigInt::from(10).pow(30u32)), integer(5), integer(7)];
+        assert!(Function::from_values(&values, 0).is_ok());
+    }
+
+    #[test]
+    fn eval() {
+        let cubic = Function::from_values(&as_ratios(vec![0, 1, 8, 27, 64]), 0).unwrap();
+        assert_eq!(cubic.eval(&ratio(1, 2)), Ok(ratio(1, 8)));
+        assert_eq!((val(1) / Independent).eval(&ratio(0, 1)), Err(EvalError::DivideByZero));
+        assert_eq!(Independent.pow(val(-1)).eval(&ratio(0, 1)), Err(EvalError::DivideByZero));
+    }
+
+    #[test]
+    fn eval_roots() {
+        let sqrt = Independent.pow(ratio(1, 2));
+        assert_eq!(sqrt.eval(&ratio(9, 4)), Ok(ratio(3, 2)));
+        assert_eq!(sqrt.eval(&ratio(2, 1)), Err(EvalError::Irrational));
+        assert_eq!(sqrt.eval(&ratio(-4, 1)), Err(EvalError::Undefined));
+        assert_eq!(val(2).pow(Independent).eval(&integer(1 << 30)), Err(EvalError::Overflow));
+    }
+
+    #[test]
+    fn eval_log() {
+        // log_2(x)
+        let log = Independent.ln() / val(2).ln();
+        assert_eq!(log.eval(&ratio(1, 8)), Ok(ratio(-3, 1)));
+        assert_eq!(log.eval(&ratio(3, 1)), Err(EvalError::Irrational));
+        assert_eq!(log.eval(&ratio(-1, 1)), Err(EvalError::Undefined));
+        assert_eq!(Independent.ln().eval(&ratio(1, 1)), Ok(ratio(0, 1)));
+        assert_eq!(Independent.ln().eval(&ratio(2, 1)), Err(EvalError::Irrational));
+    }
+
+    #[test]
+    fn eval_sin() {
+        // sin(pi*x/6)
+        let sin = (Pi * Independent / val(6)).sin();
+        assert_eq!(sin.eval(&ratio(1, 1)), Ok(ratio(1, 2)));
+        assert_eq!(sin.eval(&ratio(-3, 1)), Ok(ratio(-1, 1)));
+        assert_eq!(sin.eval(&ratio(2, 1)), Err(EvalError::Irrational));
+        assert_eq!(Independent.sin().eval(&ratio(1, 1)), Err(EvalError::Irrational));
+    }
+
+    #[test]
+    fn eval_factorial() {
+        assert_eq!(Independent.factorial().eval(&ratio(5, 1)), Ok(ratio(120, 1)));
+        assert_eq!(Independent.factorial().eval(&ratio(1, 2)), Err(EvalError::Undefined));
+        assert_eq!(Independent.factorial().eval(&integer(1 << 20)), Err(EvalError::Overflow));
+    }
+}
@@ -0,0 +1,419 @@
+use core::{convert::TryFrom, iter};
+use num::{BigInt, BigRational, Zero, One, Integer, Signed, ToPrimitive};
+use config::fmt::{FmtAble, FmtEr};
+use crate::{polynomial::Polynomial, function::Expression, predict::Predict, error::{FitError, EvalError, require}, linear_algebra::Matrix, util::{integer, format_ratio, format_term, format_base}};
+use alloc::{vec::Vec, string::{String, ToString}, boxed::Box};
+
+// A recurrence with polynomial coefficients, p0(x)a(x)+p1(x)a(x-1)+...+pr(x)a(x-r)=0, such
+// as a(x)=x*a(x-1) for the factorials. Sequences like this are called holonomic or P-recursive.
+#[derive(Debug)]
+pub struct Holonomic {
+    // coefficients[i] is the coefficient of a(x-i)
+    coefficients: Vec<Polynomial>,
+    initial: Vec<BigRational>,
+}
+impl Holonomic {
+    // Tries the fewest unknown coefficients first, and the lowest order if there is a tie
+    pub fn from_values(values: &Vec<BigRational>) -> Result<Self, FitError> {
+        require(values, 3)?;
+        let len = values.len();
+        for unknowns in 2..len {
+            for order in 1..unknowns {
+                if unknowns % (order + 1) != 0 {
+                    continue;
+                }
+                let terms = unknowns / (order + 1);
+                // The coefficients can all be multiplied by the same number, so there is one
+                // less real unknown, but there still must be one more equation than that
+                if len - order < unknowns {
+                    continue;
+                }
+                let matrix = (order..len).map(|n| {
+                    let n_ratio = &integer(n as i32);
+                    (0..=order).flat_map(|i| (0..terms as i32).map(move |j| n_ratio.pow(j) * &values[n - i])).collect()
+                }).collect();
+                let mut solutions = Matrix::from_rows(matrix).nullspace();
+                if solutions.len() != 1 {
+                    continue;
+                }
+                let mut solution: Vec<BigRational> = solutions.pop().unwrap().into();
+
+                // Scale the coefficients so that they are integers with no common factor, and
+                // the leading coefficient of p0 is positive
+                let lcm = solution.iter().fold(BigInt::one(), |lcm, c| lcm.lcm(c.denom()));
+                let mut gcd = solution.iter().fold(BigInt::zero(), |gcd, c| gcd.gcd(&(c * &lcm).to_integer()));
+                if solution[0..terms].iter().rev().find(|c| !c.is_zero()).is_some_and(|c| c.is_negative()) {
+                    gcd = -gcd;
+                }
+                let scale = BigRational::new(lcm, gcd);
+                for c in solution.iter_mut() {
+                    *c *= &scale;
+                }
+
+                let coefficients: Vec<Polynomial> = solution.chunks(terms).map(Polynomial::from_coefficients).collect::<Result<_, _>>()?;
+                // a(n) can't be found from the previous values if p0(n) is 0, which can't be ruled
+                // out if the roots of p0 can't all be found
+                if coefficients[0].is_zero() || coefficients[order].is_zero() || coefficients[0].rational_roots()
+                    .is_none_or(|roots| roots.iter().any(|root| root.is_integer() && root >= &integer(order as i32))) {
+                    continue;
+                }
+                return Ok(Self {
+                    coefficients,
+                    initial: values[0..order].to_vec(),
+                });
+            }
+        }
+        // Every recurrence that was tried either didn't fit or wasn't the only one that did
+        Err(FitError::Singular)
+    }
+    pub fn order(&self) -> usize {
+        self.coefficients.len() - 1
+    }
+    // a(0), a(1), a(2), ... p0(x) is never 0 once there are enough previous values, since
+    // from_values doesn't allow it
+    fn terms(&self) -> impl Iterator<Item = BigRational> + '_ {
+        let order = self.order();
+        let mut previous: Vec<BigRational> = Vec::with_capacity(order);
+        (0..).map(move |i| {
+            let value = if i < order {
+                self.initial[i].clone()
+            }
+            else {
+                let x = integer(i as i32);
+                -self.coefficients[1..].iter().zip(previous.iter().rev()).fold(BigRational::zero(), |sum, e| sum + e.0.eval(&x) * e.1)
+                    / self.coefficients[0].eval(&x)
+            };
+            if i >= order {
+                previous.remove(0);
+            }
+            previous.push(value.clone());
+            value
+        })
+    }
+    // If the recurrence is a(x)=r(x)a(x-1), where r is a rational function whose numerator
+    // and denominator have only rational roots, then a(x) can be written using factorials.
+    // Recurrences like a(x)=(x+m)a(x-2) give double factorials.
+    pub fn closed_form(&self) -> Option<FactorialProduct> {
+        let step = self.order();
+        if self.coefficients[1..step].iter().any(|c| !c.is_zero()) || self.initial.iter().any(|v| v.is_zero()) {
+            return None;
+        }
+        let (numer_leading, numer_roots) = self.coefficients[step].factor()?;
+        let (denom_leading, denom_roots) = self.coefficients[0].factor()?;
+        let base = -numer_leading / denom_leading;
+        match step {
+            1 => FactorialProduct::hypergeometric(&self.initial[0], base, &numer_roots, &denom_roots),
+            2 => {
+                if !base.is_one() || !denom_roots.is_empty() || numer_roots.len() != 1 || !numer_roots[0].is_integer() {
+                    return None;
+                }
+                // a(x)=(x+m)a(x-2) gives a(x)=c*(x+m)!!, if c is the same for odd and even x
+                let shift = -numer_roots[0].to_integer().to_i32()?;
+                if shift < -1 {
+                    return None;
+                }
+                let constant = &self.initial[0] / double_factorial(shift);
+                if &self.initial[1] / double_factorial(shift + 1) != constant {
+                    return None;
+                }
+                Some(FactorialProduct {
+                    constant,
+                    base,
+                    factorials: vec![(Factorial { scale: 1, shift, double: true }, 1)],
+                })
+            },
+            _ => None,
+        }
+    }
+}
+impl Predict for Holonomic {
+    fn predict(&self, x: i32) -> Result<BigRational, EvalError> {
+        if x < 0 {
+            return Err(EvalError::Undefined);
+        }
+        Ok(self.terms().nth(x as usize).unwrap())
+    }
+
+    // Each value is found from the ones before it, so they are only found once
+    fn predictions(&self, from: i32) -> Box<dyn Iterator<Item = BigRational> + '_> {
+        if from < 0 {
+            return Box::new(iter::empty());
+        }
+        Box::new(self.terms().skip(from as usize))
+    }
+}
+impl FmtAble for Holonomic {
+    fn format(&self, f: &impl FmtEr) -> String {
+        if let Some(closed_form) = self.closed_form() {
+            return closed_form.format(f);
+        }
+        let x = f.x().to_string();
+        let mut s = String::new();
+        for coefficient in self.coefficients.iter().enumerate().skip(1).filter(|c| !c.1.is_zero()) {
+            let term = (-coefficient.1).format_times(f.sequence_term(f.subtract(x.as_str(), coefficient.0.to_string().as_str()).as_str()).as_str(), f);
+            if s.is_empty() { s = term }
+            else { s = f.add(s.as_str(), term.as_str()) }
+        }
+        s = format!("{}={}", self.coefficients[0].format_times(f.sequence_term(x.as_str()).as_str(), f), s);
+        for value in self.initial.iter().enumerate() {
+            s = format!("{}, {}={}", s, f.sequence_term(value.0.to_string().as_str()), format_ratio(value.1, f));
+        }
+        s
+    }
+}
+
+// (scale*x+shift)!, or (scale*x+shift)!! if double is true
+#[derive(Clone, Debug, PartialEq)]
+struct Factorial {
+    scale: i32,
+    shift: i32,
+    double: bool,
+}
+impl Factorial {
+    fn argument(&self, f: &impl FmtEr) -> String {
+        Polynomial::linear(integer(self.shift), integer(self.scale)).format(f)
+    }
+}
+impl FmtAble for Factorial {
+    fn format(&self, f: &impl FmtEr) -> String {
+        if self.double { f.double_factorial(self.argument(f).as_str()) }
+        else { f.factorial(self.argument(f).as_str()) }
+    }
+}
+
+// c*b^x times a product of factorials of x, each raised to an integer power
+#[derive(Debug)]
+pub struct FactorialProduct {
+    constant: BigRational,
+    base: BigRational,
+    factorials: Vec<(Factorial, i32)>,
+}
+impl FactorialProduct {
+    // a(x)=b*r(x)a(x-1), where the roots of the numerator and denominator of r are given
+    fn hypergeometric(first: &BigRational, base: BigRational, numer_roots: &[BigRational], denom_roots: &[BigRational]) -> Option<Self> {
+        let mut product = Self { constant: BigRational::one(), base, factorials: Vec::new() };
+        for &(roots, sign) in [(numer_roots, 1), (denom_roots, -1)].iter() {
+            for root in roots {
+                // Each root r contributes a factor of (1-r)(2-r)...(x-r)
+                if root.is_integer() {
+                    // (1+m)(2+m)...(x+m) = (x+m)!/m!
+                    let shift = -root.to_integer().to_i32()?;
+                    if shift < 0 {
+                        return None;
+                    }
+                    product.multiply(Factorial { scale: 1, shift, double: false }, sign);
+                }
+                else if (root * integer(2)).is_integer() {
+                    // (1+q/2)(2+q/2)...(x+q/2) is the product of the odd numbers from q+2 to 2x+q,
+                    // divided by 2^x, which is a constant times (2x+q+1)!/(4^x*(x+(q+1)/2)!)
+                    let q = -(root * integer(2)).to_integer().to_i32()?;
+                    if q < -1 {
+                        return None;
+                    }
+                    product.multiply(Factorial { scale: 2, shift: q + 1, double: false }, sign);
+                    product.multiply(Factorial { scale: 1, shift: (q + 1) / 2, double: false }, -sign);
+                    product.base *= BigRational::new(1.into(), 4.into()).pow(sign);
+                }
+                else {
+                    return None;
+                }
+            }
+        }
+        // The constant is chosen so that the first value is correct
+        product.constant = first / product.factorials.iter()
+            .fold(BigRational::one(), |value, factorial| value * factorial_of(factorial.0.shift).pow(factorial.1));
+        Some(product)
+    }
+    fn multiply(&mut self, factorial: Factorial, power: i32) {
+        multiply(&mut self.factorials, factorial, power);
+    }
+}
+// Double factorials can't be written as an expression, because they aren't the same
+// function of x for odd and even x
+impl TryFrom<&FactorialProduct> for Expression<BigRational> {
+    type Error = FitError;
+
+    fn try_from(product: &FactorialProduct) -> Result<Self, FitError> {
+        if product.factorials.iter().any(|factorial| factorial.0.double) {
+            return Err(FitError::NoClosedForm);
+        }
+        let mut numerator = Vec::new();
+        let mut denominator = Vec::new();
+        if !product.base.is_one() {
+            numerator.push(Expression::Val(product.base.clone()).pow(Expression::Independent));
+        }
+        for (factorial, power) in product.factorials.iter() {
+            let mut e = Expression::from(&Polynomial::linear(integer(factorial.shift), integer(factorial.scale))).factorial();
+            if power.abs() > 1 {
+                e = e.pow(integer(power.abs()));
+            }
+            if *power > 0 { numerator.push(e) }
+            else { denominator.push(e) }
+        }
+        let product_of = |terms: Vec<Expression<BigRational>>| terms.into_iter().fold(None, |product, term| Some(match product {
+            Some(product) => product * term,
+            None => term,
+        }));
+        let mut e = product_of(numerator).unwrap_or_else(|| Expression::Val(BigRational::one()));
+        if let Some(denominator) = product_of(denominator) {
+            e = e / denominator;
+        }
+        Ok(e.times(&product.constant))
+    }
+}
+impl FmtAble for FactorialProduct {
+    fn format(&self, f: &impl FmtEr) -> String {
+        let mut factorials = self.factorials.clone();
+        let mut numerator = Vec::new();
+        let mut denominator = Vec::new();
+        if !self.base.is_one() {
+            numerator.push(f.pow(format_base(&self.base, f).as_str(), f.x().to_string().as_str()));
+        }
+        // (n)!/((k)!(n-k)!) is written as the binomial coefficient n choose k
+        while let Some(binomial) = find_binomial(&factorials) {
+            numerator.push(f.binomial(factorials[binomial.0].0.argument(f).as_str(), factorials[binomial.1].0.argument(f).as_str()));
+            let (n, k, n_minus_k) = (factorials[binomial.0].0.clone(), factorials[binomial.1].0.clone(), factorials[binomial.2].0.clone());
+            multiply(&mut factorials, n, -1);
+            multiply(&mut factorials, k, 1);
+            multiply(&mut factorials, n_minus_k, 1);
+        }
+        for factorial in factorials.iter() {
+            let mut s = factorial.0.format(f);
+            if factorial.1.abs() > 1 {
+                s = f.pow(s.as_str(), factorial.1.abs().to_string().as_str());
+            }
+            if factorial.1 > 0 { numerator.push(s) }
+            else { denominator.push(s) }
+        }
+        let product = |terms: &Vec<String>| terms.iter().skip(1).fold(terms.first().cloned().unwrap_or_default(), |s, term| f.multiply(s.as_str(), term));
+        let mut s = product(&numerator);
+        if !denominator.is_empty() {
+            s = f.fraction(if s.is_empty() { "1" } else { s.as_str() }, product(&denominator).as_str());
+        }
+        format_term(&self.constant, s.as_str(), f)
+    }
+}
+
+// Multiplies the product of factorials by factorial^power
+fn multiply(factorials: &mut Vec<(Factorial, i32)>, factorial: Factorial, power: i32) {
+    match factorials.iter().position(|e| e.0 == factorial) {
+        Some(i) => {
+            factorials[i].1 += power;
+            if factorials[i].1 == 0 {
+                factorials.remove(i);
+            }
+        },
+        None => factorials.push((factorial, power)),
+    }
+}
+
+// Finds the indices of factorials (n)!, (k)! and (n-k)!, where the first is in the numerator
+// and the others are in the denominator
+fn find_binomial(factorials: &[(Factorial, i32)]) -> Option<(usize, usize, usize)> {
+    for n in factorials.iter().enumerate().filter(|e| e.1.1 > 0 && !e.1.0.double) {
+        for k in factorials.iter().enumerate().filter(|e| e.1.1 < 0 && !e.1.0.double) {
+            let rest = Factorial { scale: n.1.0.scale - k.1.0.scale, shift: n.1.0.shift - k.1.0.shift, double: false };
+            if let Some(n_minus_k) = factorials.iter().position(|e| e.0 == rest && e.1 < 0) {
+                if n_minus_k != k.0 || k.1.1 <= -2 {
+                    return Some((n.0, k.0, n_minus_k));
+                }
+            }
+        }
+    }
+    None
+}
+
+pub(crate) fn factorial_of(n: i32) -> BigRational {
+    integer((1..=n).map(BigInt::from).product::<BigInt>())
+}
+
+// n!!, where (-1)!! and 0!! are both 1
+fn double_factorial(n: i32) -> BigRational {
+    integer((1..=n).rev().step_by(2).map(BigInt::from).product::<BigInt>())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Holonomic, factorial_of};
+    use crate::util::as_ratios;
+    use config::fmt::{formatters, FmtAble};
+    const ASCII: formatters::ASCII = formatters::ASCII;
+
+    fn central_binomial() -> Holonomic {
+        Holonomic::from_values(&as_ratios(vec![1, 2, 6, 20, 70, 252, 924])).unwrap()
+    }
+
+    fn motzkin() -> Holonomic {
+        Holonomic::from_values(&as_ratios(vec![1, 1, 2, 4, 9, 21, 51, 127, 323])).unwrap()
+    }
+
+    #[test]
+    fn factorial() {
+        assert_eq!(Holonomic::from_values(&as_ratios(vec![1, 1, 2, 6, 24, 120])).unwrap().format(&ASCII), "x!");
+    }
+
+    #[test]
+    fn large_factorial() {
+        // 13! doesn't fit in an i32
+        let values = (0..16).map(factorial_of).collect();
+        assert_eq!(Holonomic::from_values(&values).unwrap().format(&ASCII), "x!");
+    }
+
+    #[test]
+    fn shifted_factorial() {
+        assert_eq!(Holonomic::from_values(&as_ratios(vec![1, 2, 6, 24, 120, 720])).unwrap().format(&ASCII), "(x+1)!");
+    }
+
+    #[test]
+    fn exponential_factorial() {
+        assert_eq!(Holonomic::from_values(&as_ratios(vec![1, 2, 8, 48, 384, 3840])).unwrap().format(&ASCII), "2^x*x!");
+    }
+
+    #[test]
+    fn binomial() {
+        assert_eq!(central_binomial().format(&ASCII), "C(2x, x)");
+    }
+
+    #[test]
+    fn catalan() {
+        assert_eq!(Holonomic::from_values(&as_ratios(vec![1, 1, 2, 5, 14, 42, 132, 429])).unwrap().format(&ASCII), "(2x)!/(x!*(x+1)!)");
+    }
+
+    #[test]
+    fn double_factorial() {
+        assert_eq!(Holonomic::from_values(&as_ratios(vec![1, 1, 2, 3, 8, 15, 48, 105])).unwrap().format(&ASCII), "x!!");
+    }
+
+    #[test]
+    fn no_closed_form() {
+        let holonomic = Holonomic::from_values(&as_ratios(vec![1, 1, 3, 6, 18, 48, 156, 492])).unwrap();
+        assert!(holonomic.closed_form().is_none());
+        assert_eq!(holonomic.format(&ASCII), "a(x)=a(x-1)+x*a(x-2), a(0)=1, a(1)=1");
+    }
+
+    #[test]
+    fn polynomial_leading_coefficient() {
+        assert_eq!(motzkin().order(), 2);
+        assert_eq!(motzkin().format(&ASCII), "(x+2)*a(x)=(2x+1)*a(x-1)+(3x-3)*a(x-2), a(0)=1, a(1)=1");
+    }
+
+    #[test]
+    fn too_short() {
+        assert!(Holonomic::from_values(&as_ratios(vec![1, 1, 2, 6])).is_err());
+    }
+
+    #[test]
+    fn unicode() {
+        assert_eq!(central_binomial().format(&formatters::Unicode), "C(2𝑥, 𝑥)");
+    }
+
+    #[test]
+    fn java_js() {
+        assert_eq!(central_binomial().format(&formatters::Java_JS), "binomial(2*x, x)");
+    }
+
+    #[test]
+    fn latex() {
+        assert_eq!(central_binomial().format(&formatters::LaTeX), r"\binom{2x}{x}");
+    }
+}
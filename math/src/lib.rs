@@ -3,10 +3,28 @@
 #[macro_use]
 extern crate alloc;
 
+pub mod error;
 pub mod polynomial;
 pub mod exponential;
+pub mod power;
+pub mod logarithmic;
+pub mod quadratic;
+pub mod radical;
+pub mod recurrence;
+pub mod c_finite;
+pub mod holonomic;
+pub mod rational_fn;
+pub mod sinusoidal;
+pub mod absolute_value;
+pub mod piecewise;
+pub mod quasi_polynomial;
 pub mod function;
+pub mod model;
+pub mod calculus;
+pub mod parser;
+pub mod predict;
+pub mod linear_algebra;
+mod simplify;
+mod format;
 mod ratio_field;
-//pub mod fibonacci_like;
-//pub mod linear_algebra;
 mod util;
\ No newline at end of file
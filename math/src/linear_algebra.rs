@@ -1,5 +1,6 @@
-use core::{ops::{Add, Sub, Mul, Neg, Index, IndexMut}, cmp::{PartialEq}};
-use std::fmt::{self, Display, Formatter};
+use core::{ops::{Add, Sub, Mul, Neg, Index, IndexMut}, cmp::{PartialEq}, fmt::{self, Display, Formatter}};
+use num::Num;
+use alloc::{vec::Vec, string::String};
 
 // ----------------------------------------------------------------------------------------- Point
 #[derive(PartialEq, Debug, Copy, Clone)]
@@ -36,7 +37,7 @@ impl<T> Sub for Point<T>
 
 impl<T, U> Mul<U> for Point<T>
     where T: Mul<U, Output = T>,
-          U: Copy 
+          U: Copy
 {
     type Output = Self;
 
@@ -45,7 +46,7 @@ impl<T, U> Mul<U> for Point<T>
             x: self.x * other,
             y: self.y * other,
         }
-    } 
+    }
 }
 impl<T> Display for Point<T>
     where T: Display
@@ -58,7 +59,7 @@ impl<T> Display for Point<T>
 
 // ------------------------------------------------------------------------------------------------------ Vector
 #[derive(PartialEq, Debug, Clone)]
-struct Vector<T> {
+pub struct Vector<T> {
     v: Vec<T>
 }
 
@@ -77,29 +78,24 @@ impl<T> IndexMut<usize> for Vector<T> {
 }
 
 impl<T> Add for Vector<T>
-    where T: Add<T, Output = T> + Copy
+    where T: Add<T, Output = T> + Clone
 {
     type Output = Option<Self>;
 
     fn add(self, o: Self) -> Self::Output {
-        if self.len() == o.len() {
-            Some(Self {
-                v: self.v.iter().enumerate().map(|i|  *i.1 + o[i.0]).collect(),
-            })
-        }
-        else { None }
+        &self + &o
     }
 }
 
-impl<'a, 'b, T> Add<&'b Vector<T>> for &'a Vector<T>
-    where T: Add<T, Output = T> + Copy
+impl<'b, T> Add<&'b Vector<T>> for &Vector<T>
+    where T: Add<T, Output = T> + Clone
 {
     type Output = Option<Vector<T>>;
 
     fn add(self, o: &'b Vector<T>) -> Self::Output {
         if self.len() == o.len() {
             Some(Vector {
-                v: self.v.iter().enumerate().map(|i|  *i.1 + o[i.0]).collect(),
+                v: self.v.iter().enumerate().map(|i|  i.1.clone() + o[i.0].clone()).collect(),
             })
         }
         else { None }
@@ -107,29 +103,24 @@ impl<'a, 'b, T> Add<&'b Vector<T>> for &'a Vector<T>
 }
 
 impl<T> Sub for Vector<T>
-    where T: Sub<T, Output = T> + Copy
+    where T: Sub<T, Output = T> + Clone
 {
     type Output = Option<Self>;
 
     fn sub(self, o: Self) -> Self::Output {
-        if self.len() == o.len() {
-            Some(Self {
-                v: self.v.iter().enumerate().map(|i|  *i.1 - o[i.0]).collect(),
-            })
-        }
-        else { None }
+        &self - &o
     }
 }
 
-impl<'a, 'b, T> Sub<&'b Vector<T>> for &'a Vector<T>
-    where T: Sub<T, Output = T> + Copy
+impl<'b, T> Sub<&'b Vector<T>> for &Vector<T>
+    where T: Sub<T, Output = T> + Clone
 {
     type Output = Option<Vector<T>>;
 
     fn sub(self, o: &'b Vector<T>) -> Self::Output {
         if self.len() == o.len() {
             Some(Vector {
-                v: self.v.iter().enumerate().map(|i|  *i.1 - o[i.0]).collect(),
+                v: self.v.iter().enumerate().map(|i|  i.1.clone() - o[i.0].clone()).collect(),
             })
         }
         else { None }
@@ -137,57 +128,51 @@ impl<'a, 'b, T> Sub<&'b Vector<T>> for &'a Vector<T>
 }
 
 impl<T> Neg for Vector<T>
-    where T: Neg<Output = T> + Copy
+    where T: Neg<Output = T> + Clone
 {
     type Output = Self;
 
     fn neg(self) -> Self::Output {
-        Self {
-            v: self.v.iter().map(|i|  -*i).collect(),
-        }
+        -&self
     }
 }
 
-impl<'a, T> Neg for &'a Vector<T>
-    where T: Neg<Output = T> + Copy
+impl<T> Neg for &Vector<T>
+    where T: Neg<Output = T> + Clone
 {
     type Output = Vector<T>;
 
     fn neg(self) -> Self::Output {
         Vector {
-            v: self.v.iter().map(|i|  -*i).collect(),
+            v: self.v.iter().map(|i|  -i.clone()).collect(),
         }
     }
 }
 
-impl<T, U> Mul<U> for Vector<T>
-    where T: Mul<U, Output = T> + Copy,
-          U: Copy
+impl<T> Mul<T> for Vector<T>
+    where T: Mul<T, Output = T> + Clone
 {
     type Output = Self;
 
-    fn mul(self, o: U) -> Self::Output {
-        Self {
-            v: self.v.iter().map(|e| *e * o).collect(),
-        }
+    fn mul(self, o: T) -> Self::Output {
+        &self * o
     }
 }
 
-impl<'a, T, U> Mul<U> for &'a Vector<T>
-    where T: Mul<U, Output = T> + Copy,
-          U: Copy
+impl<T> Mul<T> for &Vector<T>
+    where T: Mul<T, Output = T> + Clone
 {
     type Output = Vector<T>;
 
-    fn mul(self, o: U) -> Self::Output {
+    fn mul(self, o: T) -> Self::Output {
         Vector {
-            v: self.v.iter().map(|e| *e * o).collect(),
+            v: self.v.iter().map(|e| e.clone() * o.clone()).collect(),
         }
     }
 }
 
 impl<T> Display for Vector<T>
-    where T: Display 
+    where T: Display
 {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         let mut s = String::new();
@@ -199,6 +184,12 @@ impl<T> Display for Vector<T>
     }
 }
 
+impl<T> From<Vector<T>> for Vec<T> {
+    fn from(vector: Vector<T>) -> Self {
+        vector.v
+    }
+}
+
 impl<T> Vector<T> {
     pub fn new(v: Vec<T>) -> Self {
         Self { v }
@@ -206,12 +197,26 @@ impl<T> Vector<T> {
     pub fn len(&self) -> usize {
         self.v.len()
     }
+    pub fn is_empty(&self) -> bool {
+        self.v.is_empty()
+    }
+}
+
+impl<T: Num + Clone> Vector<T> {
+    // Returns None if the vectors have different lengths
+    pub fn dot(&self, o: &Self) -> Option<T> {
+        if self.len() == o.len() {
+            Some(self.v.iter().zip(o.v.iter()).fold(T::zero(), |sum, e| sum + e.0.clone() * e.1.clone()))
+        }
+        else { None }
+    }
 }
 
 
 // ---------------------------------------------------------------------------------------------------- Matrix
+// Stored as a list of columns, and indexed by (row, column)
 #[derive(PartialEq, Debug, Clone)]
-struct Matrix<T> {
+pub struct Matrix<T> {
     m: Vec<Vector<T>>,
     rows: usize,
     columns: usize,
@@ -232,7 +237,7 @@ impl<T> IndexMut<(usize, usize)> for Matrix<T> {
 }
 
 impl<T> Add for Matrix<T>
-    where T: Add<T, Output = T> + Copy
+    where T: Add<T, Output = T> + Clone
 {
     type Output = Option<Self>;
 
@@ -248,7 +253,7 @@ impl<T> Add for Matrix<T>
 }
 
 impl<T> Sub for Matrix<T>
-    where T: Sub<T, Output = T> + Copy
+    where T: Sub<T, Output = T> + Clone
 {
     type Output = Option<Self>;
 
@@ -264,7 +269,7 @@ impl<T> Sub for Matrix<T>
 }
 
 impl<T> Neg for Matrix<T>
-    where T: Neg<Output = T> + Copy
+    where T: Neg<Output = T> + Clone
 {
     type Output = Self;
 
@@ -276,43 +281,297 @@ impl<T> Neg for Matrix<T>
     }
 }
 
-impl<T, U> Mul<U> for Matrix<T>
-    where T: Mul<U, Output = T> + Copy,
-          U: Copy
+impl<T> Mul<T> for Matrix<T>
+    where T: Mul<T, Output = T> + Clone
 {
     type Output = Self;
 
-    fn mul(self, o: U) -> Self::Output {
+    fn mul(self, o: T) -> Self::Output {
         Self {
-            m: self.m.iter().map(|e| e * o).collect(),
+            m: self.m.iter().map(|e| e * o.clone()).collect(),
             ..self
         }
     }
 }
 
-/*impl<T> Mul<Vector<T>> for Matrix<T>
-    where T: Add + Sub + Neg + Mul<T, Output = T> + Eq + Copy + Clone
+impl<'b, T> Mul<&'b Vector<T>> for &Matrix<T>
+    where T: Num + Clone
 {
     type Output = Option<Vector<T>>;
 
-    fn mul(self, o: Vector<T>) -> Self::Output {
-        if self.rows == o.v.len() {
-            let vecs = self.m.iter().enumerate().map(|i| *i.1 * o[i.0]);
-            
+    // Each entry is the dot product of a row with the vector
+    fn mul(self, o: &'b Vector<T>) -> Self::Output {
+        if self.columns == o.len() {
+            Some(Vector::new((0..self.rows).map(|row| self.row(row).dot(o).unwrap()).collect()))
+        }
+        else { None }
+    }
+}
+
+impl<'b, T> Mul<&'b Matrix<T>> for &Matrix<T>
+    where T: Num + Clone
+{
+    type Output = Option<Matrix<T>>;
+
+    fn mul(self, o: &'b Matrix<T>) -> Self::Output {
+        if self.columns == o.rows {
+            Some(Matrix::from_columns(o.m.iter().map(|column| (self * column).unwrap()).collect(), self.rows))
         }
         else { None }
     }
-}*/
+}
 
-/*impl<T> Matrix<T>
-    where T: Add + Sub + Neg + Clone + Copy + PartialEq
+impl<T> Display for Matrix<T>
+    where T: Display + Clone
 {
-    fn row(&self, i: usize) -> Vec<T> {
-        self.m.iter().map(|e| e[i]).collect()
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for row in 0..self.rows {
+            if row != 0 { writeln!(f)?; }
+            write!(f, "{}", self.row(row))?;
+        }
+        Ok(())
+    }
+}
+
+impl<T> Matrix<T> {
+    pub fn rows(&self) -> usize {
+        self.rows
     }
-    fn transpose(&self) -> Self {
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+    pub fn column(&self, i: usize) -> &Vector<T> {
+        &self.m[i]
+    }
+}
+
+impl<T: Clone> Matrix<T> {
+    // Each row must have the same length
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Self {
+        let columns = rows.first().map_or(0, |row| row.len());
+        assert!(rows.iter().all(|row| row.len() == columns), "Each row of a matrix must have the same length");
         Self {
-            m: self.m
+            m: (0..columns).map(|column| Vector::new(rows.iter().map(|row| row[column].clone()).collect())).collect(),
+            rows: rows.len(),
+            columns,
         }
     }
-}*/
\ No newline at end of file
+    // Each column must have the given number of rows, which is needed in case there are
+    // no columns
+    pub fn from_columns(columns: Vec<Vector<T>>, rows: usize) -> Self {
+        assert!(columns.iter().all(|column| column.len() == rows), "Each column of a matrix must have the same length");
+        Self {
+            columns: columns.len(),
+            m: columns,
+            rows,
+        }
+    }
+    pub fn row(&self, i: usize) -> Vector<T> {
+        Vector::new(self.m.iter().map(|e| e[i].clone()).collect())
+    }
+    pub fn transpose(&self) -> Self {
+        Self {
+            m: (0..self.rows).map(|i| self.row(i)).collect(),
+            rows: self.columns,
+            columns: self.rows,
+        }
+    }
+    fn to_rows(&self) -> Vec<Vec<T>> {
+        (0..self.rows).map(|i| self.row(i).into()).collect()
+    }
+}
+
+// All of these are exact as long as T is, such as for rational numbers, since they only
+// add, subtract, multiply and divide
+impl<T: Num + Clone> Matrix<T> {
+    pub fn identity(size: usize) -> Self {
+        Self::from_rows((0..size).map(|row| (0..size).map(|column| if row == column { T::one() } else { T::zero() }).collect()).collect())
+    }
+    // Reduces the matrix to reduced row echelon form using gauss-jordan elimination. Also
+    // returns the column of the pivot in each nonzero row.
+    pub fn rref(&self) -> (Self, Vec<usize>) {
+        let mut rows = self.to_rows();
+        let mut pivots = Vec::new();
+        for column in 0..self.columns {
+            let row = pivots.len();
+            let pivot = match (row..self.rows).find(|r| !rows[*r][column].is_zero()) {
+                Some(pivot) => pivot,
+                None => continue,
+            };
+            rows.swap(row, pivot);
+            let divisor = rows[row][column].clone();
+            for e in rows[row].iter_mut() {
+                *e = e.clone() / divisor.clone();
+            }
+            let pivot_row = rows[row].clone();
+            for (other, other_row) in rows.iter_mut().enumerate() {
+                if other != row && !other_row[column].is_zero() {
+                    let factor = other_row[column].clone();
+                    for (e, p) in other_row.iter_mut().zip(pivot_row.iter()).skip(column) {
+                        *e = e.clone() - p.clone() * factor.clone();
+                    }
+                }
+            }
+            pivots.push(column);
+        }
+        let reduced = (0..self.columns).map(|column| Vector::new(rows.iter().map(|row| row[column].clone()).collect())).collect();
+        (Self::from_columns(reduced, self.rows), pivots)
+    }
+    pub fn rank(&self) -> usize {
+        self.rref().1.len()
+    }
+    // Returns None if the matrix isn't square
+    pub fn determinant(&self) -> Option<T> {
+        if self.rows != self.columns {
+            return None;
+        }
+        let mut rows = self.to_rows();
+        let mut determinant = T::one();
+        for column in 0..self.columns {
+            let pivot = match (column..self.rows).find(|r| !rows[*r][column].is_zero()) {
+                Some(pivot) => pivot,
+                None => return Some(T::zero()),
+            };
+            // Swapping two rows negates the determinant
+            if pivot != column {
+                rows.swap(column, pivot);
+                determinant = T::zero() - determinant;
+            }
+            determinant = determinant * rows[column][column].clone();
+            let pivot_row = rows[column].clone();
+            for row in rows.iter_mut().skip(column + 1) {
+                let factor = row[column].clone() / pivot_row[column].clone();
+                for (e, p) in row.iter_mut().zip(pivot_row.iter()).skip(column) {
+                    *e = e.clone() - p.clone() * factor.clone();
+                }
+            }
+        }
+        Some(determinant)
+    }
+    // Returns None if the matrix isn't square or isn't invertible
+    pub fn inverse(&self) -> Option<Self> {
+        if self.rows != self.columns {
+            return None;
+        }
+        let size = self.rows;
+        // The empty matrix is its own inverse
+        if size == 0 {
+            return Some(self.clone());
+        }
+        let augmented = Self::from_columns(self.m.iter().cloned().chain(Self::identity(size).m).collect(), size);
+        let (reduced, pivots) = augmented.rref();
+        if pivots.len() < size || pivots[size - 1] >= size {
+            return None;
+        }
+        Some(Self::from_columns(reduced.m[size..].to_vec(), size))
+    }
+    // Finds the only x where self * x = b. Returns None if there is no solution or more
+    // than one, or if b has the wrong length.
+    pub fn solve(&self, b: &Vector<T>) -> Option<Vector<T>> {
+        if b.len() != self.rows {
+            return None;
+        }
+        let augmented = Self::from_columns(self.m.iter().cloned().chain(core::iter::once(b.clone())).collect(), self.rows);
+        let (reduced, pivots) = augmented.rref();
+        // A pivot in the last column means that 0 = 1, and a column without a pivot is a free variable
+        if pivots.len() != self.columns || pivots.last() == Some(&self.columns) {
+            return None;
+        }
+        let mut x = Vector::new(vec![T::zero(); self.columns]);
+        for (row, pivot) in pivots.iter().enumerate() {
+            x[*pivot] = reduced[(row, self.columns)].clone();
+        }
+        Some(x)
+    }
+    // Finds a basis for the solutions of self * v = 0, with one vector for each free column
+    pub fn nullspace(&self) -> Vec<Vector<T>> {
+        let (reduced, pivots) = self.rref();
+        (0..self.columns).filter(|column| !pivots.contains(column)).map(|free| {
+            let mut v = Vector::new(vec![T::zero(); self.columns]);
+            v[free] = T::one();
+            for (row, pivot) in pivots.iter().enumerate() {
+                v[*pivot] = T::zero() - reduced[(row, free)].clone();
+            }
+            v
+        }).collect()
+    }
+    // Finds the x that minimizes the squared length of self * x - b, by solving the normal
+    // equations transpose(self) * self * x = transpose(self) * b. Returns None if the columns
+    // aren't linearly independent, since then there is more than one such x.
+    pub fn least_squares(&self, b: &Vector<T>) -> Option<Vector<T>> {
+        let transpose = self.transpose();
+        (&transpose * self)?.solve(&(&transpose * b)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Matrix, Vector};
+    use crate::util::{as_ratios, ratio};
+    use num::BigRational;
+    use alloc::vec::Vec;
+
+    fn matrix(rows: Vec<Vec<i32>>) -> Matrix<BigRational> {
+        Matrix::from_rows(rows.into_iter().map(as_ratios).collect())
+    }
+
+    fn vector(v: Vec<i32>) -> Vector<BigRational> {
+        Vector::new(as_ratios(v))
+    }
+
+    #[test]
+    fn multiply() {
+        let a = matrix(vec![vec![1, 2], vec![3, 4], vec![5, 6]]);
+        assert_eq!(&a * &vector(vec![1, -1]), Some(vector(vec![-1, -1, -1])));
+        assert_eq!(&a.transpose() * &a, Some(matrix(vec![vec![35, 44], vec![44, 56]])));
+        assert_eq!(&a * &a, None);
+    }
+
+    #[test]
+    fn rref() {
+        let (reduced, pivots) = matrix(vec![vec![1, 2, 3], vec![2, 4, 7], vec![1, 2, 4]]).rref();
+        assert_eq!(reduced, matrix(vec![vec![1, 2, 0], vec![0, 0, 1], vec![0, 0, 0]]));
+        assert_eq!(pivots, vec![0, 2]);
+    }
+
+    #[test]
+    fn rank() {
+        assert_eq!(matrix(vec![vec![1, 2, 3], vec![2, 4, 6]]).rank(), 1);
+        assert_eq!(Matrix::<BigRational>::identity(3).rank(), 3);
+    }
+
+    #[test]
+    fn determinant() {
+        assert_eq!(matrix(vec![vec![0, 1, 2], vec![3, 4, 5], vec![6, 7, 9]]).determinant(), Some(ratio(-3, 1)));
+        assert_eq!(matrix(vec![vec![1, 2], vec![2, 4]]).determinant(), Some(ratio(0, 1)));
+        assert_eq!(matrix(vec![vec![1, 2]]).determinant(), None);
+    }
+
+    #[test]
+    fn inverse() {
+        let inverse = matrix(vec![vec![2, 1], vec![1, 1]]).inverse();
+        assert_eq!(inverse, Some(matrix(vec![vec![1, -1], vec![-1, 2]])));
+        assert_eq!(matrix(vec![vec![1, 2], vec![2, 4]]).inverse(), None);
+        assert_eq!(matrix(vec![]).inverse(), Some(matrix(vec![])));
+    }
+
+    #[test]
+    fn solve() {
+        assert_eq!(matrix(vec![vec![0, 1], vec![2, 1]]).solve(&vector(vec![3, 5])), Some(vector(vec![1, 3])));
+        assert_eq!(matrix(vec![vec![1, 2], vec![2, 4]]).solve(&vector(vec![3, 6])), None);
+        assert_eq!(matrix(vec![vec![1, 0], vec![1, 0]]).solve(&vector(vec![1, 2])), None);
+    }
+
+    #[test]
+    fn nullspace() {
+        assert_eq!(matrix(vec![vec![1, 2, 3], vec![2, 4, 6]]).nullspace(), vec![vector(vec![-2, 1, 0]), vector(vec![-3, 0, 1])]);
+        assert!(matrix(vec![vec![1, 0], vec![0, 1]]).nullspace().is_empty());
+    }
+
+    #[test]
+    fn least_squares() {
+        // The line of best fit through (0, 0), (1, 1) and (2, 1) is y = x/2 + 1/6
+        let a = matrix(vec![vec![0, 1], vec![1, 1], vec![2, 1]]);
+        assert_eq!(a.least_squares(&vector(vec![0, 1, 1])), Some(Vector::new(vec![ratio(1, 2), ratio(1, 6)])));
+    }
+}
@@ -0,0 +1,265 @@
+use core::ops::{Add, Mul};
+use num::{BigRational, Zero, One, Signed, ToPrimitive};
+use config::fmt::{FmtAble, FmtEr};
+use crate::{polynomial::Polynomial, function::Expression, predict::Predict, error::{FitError, EvalError, require_unknowns, distinct, check}, util::{root, integer, as_points, format_ratio, format_term}};
+use alloc::{vec::Vec, string::String};
+
+// The largest total degree of the equation that is solved to find the shift
+const MAX_DEGREE: i32 = 12;
+
+// An exact number c*log_b(r)+q. The logarithm is kept unevaluated, because it is usually
+// irrational, unless r is a rational power of b.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Log {
+    coefficient: BigRational,
+    base: BigRational,
+    argument: BigRational,
+    constant: BigRational,
+}
+impl Log {
+    // log_b(r), which is undefined unless b and r are positive and b isn't 1
+    pub fn new(base: BigRational, argument: BigRational) -> Option<Self> {
+        if !base.is_positive() || base.is_one() || !argument.is_positive() {
+            return None;
+        }
+        Some(match rational_log(&base, &argument) {
+            Some(value) => Self { coefficient: BigRational::zero(), base, argument: BigRational::one(), constant: value },
+            None => Self { coefficient: BigRational::one(), base, argument, constant: BigRational::zero() },
+        })
+    }
+    // Returns the value if it is rational
+    pub fn to_ratio(&self) -> Option<BigRational> {
+        if self.coefficient.is_zero() {
+            Some(self.constant.clone())
+        }
+        else {
+            None
+        }
+    }
+}
+impl Add<BigRational> for Log {
+    type Output = Self;
+
+    fn add(mut self, rhs: BigRational) -> Self {
+        self.constant += rhs;
+        self
+    }
+}
+impl Mul<BigRational> for Log {
+    type Output = Self;
+
+    fn mul(mut self, rhs: BigRational) -> Self {
+        self.coefficient *= &rhs;
+        self.constant *= rhs;
+        self
+    }
+}
+impl FmtAble for Log {
+    fn format(&self, f: &impl FmtEr) -> String {
+        if self.coefficient.is_zero() {
+            return format_ratio(&self.constant, f);
+        }
+        let mut s = format_term(&self.coefficient, f.log(format_ratio(&self.base, f).as_str(), format_ratio(&self.argument, f).as_str()).as_str(), f);
+        if !self.constant.is_zero() {
+            s = f.add(s.as_str(), format_ratio(&self.constant, f).as_str());
+        }
+        s
+    }
+}
+
+// a*log_b((x-h)/d)+k, where d is only used if log_b(d) is irrational
+#[derive(Debug)]
+pub struct Logarithmic {
+    stretch: BigRational,
+    base: BigRational,
+    shift: BigRational,
+    divisor: BigRational,
+    constant: BigRational,
+}
+impl Logarithmic {
+    pub fn from_values(values: &Vec<BigRational>, starting: i32) -> Result<Self, FitError> {
+        Self::from_points(&as_points(values, starting))
+    }
+    // If y = a*log_b(x-h)+k, then (y2-y0)/(y1-y0) = e = p/q means that
+    // ((x2-h)/(x0-h))^q = ((x1-h)/(x0-h))^p, which is a polynomial equation in h. Each of
+    // its rational roots is tried as the shift.
+    pub fn from_points(points: &Vec<(BigRational, BigRational)>) -> Result<Self, FitError> {
+        // The base and stretch together only count as one unknown
+        require_unknowns(points, 3)?;
+        distinct(points)?;
+        let first = &points[0];
+        let second = points.iter().find(|point| point.1 != first.1).ok_or(FitError::Singular)?;
+        let mut error = FitError::Singular;
+        for third in points.iter().filter(|point| point.1 != first.1 && point.1 != second.1) {
+            let e = (&third.1 - &first.1) / (&second.1 - &first.1);
+            let (p, q) = match (e.numer().to_i32(), e.denom().to_i32()) {
+                (Some(p), Some(q)) if p.abs() + q <= MAX_DEGREE => (p, q),
+                _ => continue,
+            };
+            let (lhs, rhs) = if p > 0 {
+                (multiply(&linear_power(&third.0, q), &linear_power(&first.0, p)), multiply(&linear_power(&second.0, p), &linear_power(&first.0, q)))
+            }
+            else {
+                (multiply(&linear_power(&third.0, q), &linear_power(&second.0, -p)), linear_power(&first.0, q - p))
+            };
+            let equation = Polynomial::from_coefficients(&lhs.iter().zip(rhs.iter()).map(|e| e.0 - e.1).collect::<Vec<_>>())?;
+            if equation.is_zero() {
+                continue;
+            }
+            for shift in equation.rational_roots().ok_or(FitError::Overflow)? {
+                match Self::with_shift(points, first, second, shift) {
+                    Ok(new) => return Ok(new),
+                    Err(e) => error = error.closest(e),
+                }
+            }
+            // Only one equation is needed, because the real shift must be a root of all of them
+            return Err(error);
+        }
+        Err(error)
+    }
+    fn with_shift(points: &Vec<(BigRational, BigRational)>, first: &(BigRational, BigRational), second: &(BigRational, BigRational), shift: BigRational) -> Result<Self, FitError> {
+        // Every point must be in the domain
+        check(points.iter().map(|point| point.0 > shift))?;
+        let start = &first.0 - &shift;
+        let (base, power) = perfect_power(&((&second.0 - &shift) / &start)).ok_or(FitError::Singular)?;
+        let stretch = (&second.1 - &first.1) / integer(power);
+        let new = match Log::new(base.clone(), start.clone()).and_then(|log| log.to_ratio()) {
+            Some(log) => Self { constant: &first.1 - &stretch * log, stretch, base, shift, divisor: BigRational::one() },
+            None => Self { stretch, base, shift, divisor: start, constant: first.1.clone() },
+        };
+        check(points.iter().map(|point| new.apply(&point.0).and_then(|y| y.to_ratio()).as_ref() == Some(&point.1)))?;
+        Ok(new)
+    }
+    pub fn apply(&self, x: &BigRational) -> Option<Log> {
+        Some(Log::new(self.base.clone(), (x - &self.shift) / &self.divisor)? * self.stretch.clone() + self.constant.clone())
+    }
+}
+// log_b(u) is ln(u)/ln(b), where ln(b) is left unevaluated
+impl From<&Logarithmic> for Expression<BigRational> {
+    fn from(logarithmic: &Logarithmic) -> Self {
+        let mut argument = Expression::from(&Polynomial::linear(-&logarithmic.shift, BigRational::one()));
+        if !logarithmic.divisor.is_one() {
+            argument = argument / Expression::Val(logarithmic.divisor.clone());
+        }
+        (argument.ln() / Expression::Val(logarithmic.base.clone()).ln()).times(&logarithmic.stretch).plus(&logarithmic.constant)
+    }
+}
+impl Predict for Logarithmic {
+    fn predict(&self, x: i32) -> Result<BigRational, EvalError> {
+        self.apply(&integer(x)).ok_or(EvalError::Undefined)?.to_ratio().ok_or(EvalError::Irrational)
+    }
+}
+impl FmtAble for Logarithmic {
+    fn format(&self, f: &impl FmtEr) -> String {
+        let mut argument = Polynomial::linear(-&self.shift, BigRational::one()).format(f);
+        if !self.divisor.is_one() {
+            argument = f.fraction(argument.as_str(), format_ratio(&self.divisor, f).as_str());
+        }
+        let mut s = format_term(&self.stretch, f.log(format_ratio(&self.base, f).as_str(), argument.as_str()).as_str(), f);
+        if !self.constant.is_zero() {
+            s = f.add(s.as_str(), format_ratio(&self.constant, f).as_str());
+        }
+        s
+    }
+}
+
+// Finds g and n, where r = g^n, g > 1, and n is as large as possible, or None if r is 1
+fn perfect_power(r: &BigRational) -> Option<(BigRational, i32)> {
+    if r.is_one() || !r.is_positive() {
+        return None;
+    }
+    let sign = if r > &BigRational::one() { 1 } else { -1 };
+    let r = if sign == 1 { r.clone() } else { r.recip() };
+    // g is at least 2, so n can't be more than the number of bits in the numerator
+    (1..=r.numer().bits() as i32).rev().find_map(|n| root(&r, n as u32).map(|g| (g, sign * n)))
+}
+
+// Returns log_b(r) if it is rational, which is only the case if b and r are powers of the
+// same number
+pub(crate) fn rational_log(base: &BigRational, argument: &BigRational) -> Option<BigRational> {
+    if argument.is_one() {
+        return Some(BigRational::zero());
+    }
+    let (base_root, base_power) = perfect_power(base)?;
+    let (argument_root, argument_power) = perfect_power(argument)?;
+    if base_root == argument_root {
+        Some(BigRational::new(argument_power.into(), base_power.into()))
+    }
+    else {
+        None
+    }
+}
+
+// The coefficients of (c-h)^n, as a polynomial in h
+fn linear_power(c: &BigRational, n: i32) -> Vec<BigRational> {
+    (0..n).fold(vec![BigRational::one()], |product, _| multiply(&product, &[c.clone(), -BigRational::one()]))
+}
+
+// Multiplies two polynomials, where each is a list of coefficients with the lowest degree first
+fn multiply(a: &[BigRational], b: &[BigRational]) -> Vec<BigRational> {
+    let mut product = vec![BigRational::zero(); a.len() + b.len() - 1];
+    for i in a.iter().enumerate() {
+        for j in b.iter().enumerate() {
+            product[i.0 + j.0] += i.1 * j.1;
+        }
+    }
+    product
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Log, Logarithmic};
+    use crate::util::{as_ratios, ratio};
+    use config::fmt::{formatters, FmtAble};
+    use num::BigRational;
+    use alloc::vec::Vec;
+    const ASCII: formatters::ASCII = formatters::ASCII;
+
+    fn points(x: Vec<i32>, y: Vec<i32>) -> Vec<(BigRational, BigRational)> {
+        as_ratios(x).into_iter().zip(as_ratios(y)).collect()
+    }
+
+    fn parent() -> Logarithmic {
+        Logarithmic::from_points(&points(vec![1, 2, 4, 8], vec![0, 1, 2, 3])).unwrap()
+    }
+
+    #[test]
+    fn base_2() {
+        assert_eq!(parent().format(&ASCII), "log_2(x)");
+    }
+
+    #[test]
+    fn shifted() {
+        assert_eq!(Logarithmic::from_points(&points(vec![2, 3, 5, 9], vec![0, 1, 2, 3])).unwrap().format(&ASCII), "log_2(x-1)");
+    }
+
+    #[test]
+    fn stretched_and_translated() {
+        assert_eq!(Logarithmic::from_points(&points(vec![1, 3, 9, 27], vec![5, 3, 1, -1])).unwrap().format(&ASCII), "-2*log_3(x)+5");
+    }
+
+    #[test]
+    fn irrational_constant() {
+        assert_eq!(Logarithmic::from_points(&points(vec![3, 6, 12, 24], vec![0, 1, 2, 3])).unwrap().format(&ASCII), "log_2(x/3)");
+    }
+
+    #[test]
+    fn linear() {
+        assert!(Logarithmic::from_points(&points(vec![1, 2, 3, 4], vec![1, 2, 3, 4])).is_err());
+    }
+
+    #[test]
+    fn symbolic() {
+        let three = parent().apply(&ratio(3, 1)).unwrap();
+        assert_eq!(three.to_ratio(), None);
+        assert_eq!(three.format(&ASCII), "log_2(3)");
+        assert_eq!(Log::new(ratio(4, 1), ratio(8, 1)).unwrap().to_ratio(), Some(ratio(3, 2)));
+        assert_eq!(Log::new(ratio(2, 1), ratio(1, 4)).unwrap().to_ratio(), Some(ratio(-2, 1)));
+    }
+
+    #[test]
+    fn undefined() {
+        assert!(Log::new(ratio(1, 1), ratio(2, 1)).is_none());
+        assert!(parent().apply(&ratio(0, 1)).is_none());
+    }
+}
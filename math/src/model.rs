@@ -0,0 +1,198 @@
+use core::{cell::Cell, convert::TryFrom};
+use num::{BigRational, One};
+use config::fmt::{FmtAble, FmtEr};
+use crate::{
+    polynomial::Polynomial,
+    exponential::Exponential,
+    power::Power,
+    logarithmic::Logarithmic,
+    quadratic::Quadratic,
+    radical::Radical,
+    recurrence::Recurrence,
+    c_finite::CFinite,
+    holonomic::Holonomic,
+    rational_fn::RationalFunction,
+    sinusoidal::Sinusoidal,
+    absolute_value::{AbsoluteValue, PiecewiseLinear},
+    piecewise::Piecewise,
+    quasi_polynomial::QuasiPolynomial,
+    function::{Expression, starting},
+    predict::Predict,
+    error::{FitError, EvalError},
+    util::{integer, as_points},
+};
+use alloc::{vec::Vec, string::String, boxed::Box};
+
+// The pattern that some points follow, along with the family it came from, so that it can be
+// formatted and used for predictions in its own way. The families that are found from values
+// at consecutive integers are in terms of the index of each value, starting at 0.
+#[derive(Debug)]
+pub enum Model {
+    Exponential(Exponential),
+    Polynomial(Polynomial),
+    Power(Power),
+    Logarithmic(Logarithmic),
+    Radical(Radical),
+    Sinusoidal(Sinusoidal),
+    AbsoluteValue(AbsoluteValue),
+    PiecewiseLinear(PiecewiseLinear),
+    QuasiPolynomial(QuasiPolynomial),
+    CFinite(CFinite),
+    Holonomic(Holonomic),
+    Recurrence(Recurrence),
+    RationalFunction(RationalFunction),
+    Piecewise(Piecewise),
+}
+impl Model {
+    pub fn from_values(values: &Vec<BigRational>, starting: i32) -> Result<Self, FitError> {
+        Self::from_points(&as_points(values, starting))
+    }
+    pub fn from_points(points: &Vec<(BigRational, BigRational)>) -> Result<Self, FitError> {
+        Self::first(points, Some)
+    }
+    // Tries each family of functions in order, and returns the first one that fits and that
+    // accept() turns into Some. The families that can only be found from values at
+    // consecutive integers are skipped unless the points are at consecutive integers.
+    pub(crate) fn first<T>(points: &Vec<(BigRational, BigRational)>, accept: impl Fn(Self) -> Option<T>) -> Result<T, FitError> {
+        let values: Vec<BigRational> = points.iter().map(|point| point.1.clone()).collect();
+        let start = starting(points);
+        // Kept if it has too few points to be meaningful, since it is the fallback
+        let polynomial = Cell::new(None);
+        let radical_points = points.iter().map(|point| (point.0.clone(), Quadratic::from(point.1.clone()))).collect();
+        // A holonomic recurrence is preferred over the families after it if it has a closed
+        // form, and is tried again after them otherwise, so it is only fit once
+        let holonomic = Cell::new(None);
+        let found = start.and_then(|_| Exponential::from_values(&values).ok()).and_then(|e| accept(Model::Exponential(e)))
+            // A polynomial can always be found, but it is only meaningful if there are more points than coefficients
+            .or_else(|| match Polynomial::from_points(points) {
+                Ok(p) if (p.degree() as usize) + 1 < points.len() => accept(Model::Polynomial(p)),
+                p => {
+                    polynomial.set(Some(p));
+                    None
+                },
+            })
+            .or_else(|| Power::from_points(points).ok().and_then(|p| accept(Model::Power(p))))
+            .or_else(|| Logarithmic::from_points(points).ok().and_then(|l| accept(Model::Logarithmic(l))))
+            .or_else(|| Radical::from_points(&radical_points).ok().and_then(|r| accept(Model::Radical(r))))
+            .or_else(|| start.and_then(|_| Sinusoidal::from_values(&values).ok()).and_then(|s| accept(Model::Sinusoidal(s))))
+            .or_else(|| AbsoluteValue::from_points(points).ok().and_then(|a| accept(Model::AbsoluteValue(a))))
+            .or_else(|| PiecewiseLinear::from_points(points).ok().and_then(|p| accept(Model::PiecewiseLinear(p))))
+            .or_else(|| start.and_then(|start| QuasiPolynomial::from_values(&values, start).ok()).and_then(|q| accept(Model::QuasiPolynomial(q))))
+            .or_else(|| start.and_then(|_| CFinite::from_values(&values).ok()).and_then(|c| accept(Model::CFinite(c))))
+            .or_else(|| match start.and_then(|_| Holonomic::from_values(&values).ok()) {
+                Some(h) if h.closed_form().is_some() => accept(Model::Holonomic(h)),
+                h => {
+                    holonomic.set(h);
+                    None
+                },
+            })
+            .or_else(|| start.and_then(|_| Recurrence::from_values(&values).ok()).and_then(|r| accept(Model::Recurrence(r))))
+            .or_else(|| RationalFunction::from_points(points).ok().and_then(|r| accept(Model::RationalFunction(r))))
+            .or_else(|| holonomic.take().and_then(|h| accept(Model::Holonomic(h))))
+            .or_else(|| start.and_then(|start| Piecewise::from_values(&values, start).ok()).and_then(|p| accept(Model::Piecewise(p))));
+        match found {
+            Some(found) => Ok(found),
+            // Any points can be fit by a polynomial, so its error is the most basic reason
+            // that nothing else was found
+            None => {
+                let polynomial = polynomial.take().unwrap_or_else(|| Polynomial::from_points(points))?;
+                accept(Model::Polynomial(polynomial)).ok_or(FitError::NoClosedForm)
+            },
+        }
+    }
+    // The formula in terms of x, where the first value is at start, or None for recurrences
+    // and piecewise functions, which aren't functions of x
+    pub(crate) fn expression(&self, start: i32) -> Option<Expression<BigRational>> {
+        // Changes an expression in terms of the index of each value into one in terms of x
+        let indexed = |expr: Expression<BigRational>| match start {
+            0 => expr,
+            start => expr.substitute(&Expression::from(&Polynomial::linear(integer(-start), BigRational::one()))),
+        };
+        match self {
+            Model::Exponential(e) => Some(indexed(Expression::from(e))),
+            Model::Polynomial(p) => Some(Expression::from(p)),
+            Model::Power(p) => Some(Expression::from(p)),
+            Model::Logarithmic(l) => Some(Expression::from(l)),
+            Model::Radical(r) => Some(Expression::from(r)),
+            Model::Sinusoidal(s) => Some(indexed(Expression::from(s))),
+            Model::AbsoluteValue(a) => Some(Expression::from(a)),
+            Model::PiecewiseLinear(p) => Some(Expression::from(p)),
+            Model::QuasiPolynomial(q) => q.floor_form().map(|f| Expression::from(&f)),
+            Model::CFinite(c) => Some(indexed(Expression::from(c))),
+            Model::Holonomic(h) => h.closed_form().and_then(|c| Expression::try_from(&c).ok()).map(indexed),
+            Model::RationalFunction(r) => Some(Expression::from(r)),
+            Model::Recurrence(_) | Model::Piecewise(_) => None,
+        }
+    }
+    fn inner(&self) -> &dyn Predict {
+        match self {
+            Model::Exponential(e) => e,
+            Model::Polynomial(p) => p,
+            Model::Power(p) => p,
+            Model::Logarithmic(l) => l,
+            Model::Radical(r) => r,
+            Model::Sinusoidal(s) => s,
+            Model::AbsoluteValue(a) => a,
+            Model::PiecewiseLinear(p) => p,
+            Model::QuasiPolynomial(q) => q,
+            Model::CFinite(c) => c,
+            Model::Holonomic(h) => h,
+            Model::Recurrence(r) => r,
+            Model::RationalFunction(r) => r,
+            Model::Piecewise(p) => p,
+        }
+    }
+}
+impl Predict for Model {
+    fn predict(&self, x: i32) -> Result<BigRational, EvalError> {
+        self.inner().predict(x)
+    }
+    fn predictions(&self, from: i32) -> Box<dyn Iterator<Item = BigRational> + '_> {
+        self.inner().predictions(from)
+    }
+}
+impl FmtAble for Model {
+    fn format(&self, f: &impl FmtEr) -> String {
+        match self {
+            Model::Exponential(e) => e.format(f),
+            Model::Polynomial(p) => p.format(f),
+            Model::Power(p) => p.format(f),
+            Model::Logarithmic(l) => l.format(f),
+            Model::Radical(r) => r.format(f),
+            Model::Sinusoidal(s) => s.format(f),
+            Model::AbsoluteValue(a) => a.format(f),
+            Model::PiecewiseLinear(p) => p.format(f),
+            Model::QuasiPolynomial(q) => q.format(f),
+            Model::CFinite(c) => c.format(f),
+            Model::Holonomic(h) => h.format(f),
+            Model::Recurrence(r) => r.format(f),
+            Model::RationalFunction(r) => r.format(f),
+            Model::Piecewise(p) => p.format(f),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Model;
+    use crate::{predict::Predict, util::as_ratios};
+    use config::fmt::{formatters, FmtAble};
+    const ASCII: formatters::ASCII = formatters::ASCII;
+
+    // Recurrences aren't functions of x, but they are still found
+    #[test]
+    fn recurrence() {
+        let fibonacci = Model::from_values(&as_ratios(vec![1, 1, 2, 3, 5, 8, 13]), 0).unwrap();
+        assert!(matches!(fibonacci, Model::CFinite(_)));
+        let model = Model::from_values(&as_ratios(vec![1, 2, 3, 6, 11, 20, 37, 68]), 0).unwrap();
+        assert!(matches!(model, Model::Recurrence(_)));
+        assert_eq!(model.predictions(8).take(2).collect::<alloc::vec::Vec<_>>(), as_ratios(vec![125, 230]));
+    }
+
+    #[test]
+    fn holonomic() {
+        let factorials = Model::from_values(&as_ratios(vec![1, 1, 2, 6, 24, 120]), 0).unwrap();
+        assert!(matches!(factorials, Model::Holonomic(_)));
+        assert_eq!(factorials.format(&ASCII), "x!");
+    }
+}
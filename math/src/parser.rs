@@ -0,0 +1,484 @@
+use core::{str::FromStr, iter::Peekable};
+use num::{BigRational, One, Zero};
+use crate::{function::{Function, Expression::{self, *}}, error::ParseError, util::integer};
+use alloc::{vec::Vec, string::{String, ToString}};
+
+// Reads the formulas written by each of the formatters in config::fmt, so that a formula can
+// be typed in, evaluated, or checked against data. Multiplication can be written with *, ·,
+// \cdot, or by putting two factors next to each other, as in 2x or (x+1)(x-1). Powers can be
+// written with ^, superscripts or Math.pow, and LaTeX braces are treated like parentheses.
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(BigRational),
+    // A variable, constant or function, such as x, pi, sqrt or Math.pow
+    Name(String),
+    // An operator or bracket
+    Symbol(char),
+    // An exponent written in superscripts, such as ² or ⁻¹ᐟ²
+    Superscript(Expression<BigRational>),
+    // A log base written in subscripts
+    Subscript(BigRational),
+}
+
+const SUPERSCRIPTS: [(char, char); 16] = [
+    ('⁰', '0'), ('¹', '1'), ('²', '2'), ('³', '3'), ('⁴', '4'), ('⁵', '5'), ('⁶', '6'),
+    ('⁷', '7'), ('⁸', '8'), ('⁹', '9'), ('⁻', '-'), ('⁺', '+'), ('⁽', '('), ('⁾', ')'),
+    ('ᐟ', '/'), ('ˣ', 'x'),
+];
+
+fn normal_superscript(c: char) -> Option<char> {
+    SUPERSCRIPTS.iter().find(|pair| pair.0 == c).map(|pair| pair.1)
+}
+
+fn normal_subscript(c: char) -> Option<char> {
+    ('₀'..='₉').position(|sub| sub == c).and_then(|digit| core::char::from_digit(digit as u32, 10))
+}
+
+// Pairs each token with the index of the char that it starts at
+fn tokenize(s: &str) -> Result<Vec<(Token, usize)>, ParseError> {
+    let mut tokens = Vec::new();
+    let mut chars = s.chars().enumerate().peekable();
+    while let Some((i, c)) = chars.next() {
+        let token = match c {
+            _ if c.is_whitespace() => continue,
+            '0'..='9' | '.' => {
+                let number = take_while(c, &mut chars, |c| c.is_ascii_digit() || c == '.');
+                Token::Number(util::parse(number.as_str()).map_err(|_| ParseError::Unexpected(i))?)
+            },
+            _ if c.is_ascii_alphabetic() => Token::Name(take_while(c, &mut chars, |c| c.is_ascii_alphanumeric() || c == '.')),
+            // LaTeX commands
+            '\\' => {
+                let command = take_while(c, &mut chars, |c| c.is_ascii_alphabetic());
+                match command.as_str() {
+                    r"\left" | r"\right" => continue,
+                    r"\cdot" | r"\times" => Token::Symbol('*'),
+                    r"\lfloor" => Token::Symbol('⌊'),
+                    r"\rfloor" => Token::Symbol('⌋'),
+                    r"\lceil" => Token::Symbol('⌈'),
+                    r"\rceil" => Token::Symbol('⌉'),
+                    _ => Token::Name(command[1..].to_string()),
+                }
+            },
+            '𝑥' => Token::Name(String::from("x")),
+            'π' => Token::Name(String::from("pi")),
+            '·' | '×' => Token::Symbol('*'),
+            '−' => Token::Symbol('-'),
+            '{' => Token::Symbol('('),
+            '}' => Token::Symbol(')'),
+            '+' | '-' | '*' | '/' | '^' | '(' | ')' | '|' | '!' | ',' | '_' | '√' | '⌊' | '⌋' | '⌈' | '⌉' => Token::Symbol(c),
+            _ if normal_superscript(c).is_some() => {
+                let mut exponent: String = normal_superscript(c).into_iter().collect();
+                while let Some(c) = chars.peek().and_then(|c| normal_superscript(c.1)) {
+                    exponent.push(c);
+                    chars.next();
+                }
+                Token::Superscript(exponent.parse().map_err(|_| ParseError::Unexpected(i))?)
+            },
+            _ if normal_subscript(c).is_some() => {
+                let mut base: String = normal_subscript(c).into_iter().collect();
+                while let Some(c) = chars.peek().and_then(|c| normal_subscript(c.1)) {
+                    base.push(c);
+                    chars.next();
+                }
+                Token::Subscript(util::parse(base.as_str()).map_err(|_| ParseError::Unexpected(i))?)
+            },
+            _ => return Err(ParseError::Unexpected(i)),
+        };
+        tokens.push((token, i));
+    }
+    Ok(tokens)
+}
+
+// Starts with first, and adds chars while they match
+fn take_while(first: char, chars: &mut Peekable<impl Iterator<Item = (usize, char)>>, matches: impl Fn(char) -> bool) -> String {
+    let mut s: String = core::iter::once(first).collect();
+    while let Some(&(_, c)) = chars.peek() {
+        if !matches(c) {
+            break;
+        }
+        s.push(c);
+        chars.next();
+    }
+    s
+}
+
+// A recursive descent parser, with one method for each level of precedence
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    position: usize,
+    // Inside of |...|, a | ends the absolute value rather than starting a new factor
+    abs_depth: usize,
+}
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|token| &token.0)
+    }
+    fn next(&mut self) -> Result<Token, ParseError> {
+        let token = self.peek().cloned().ok_or(ParseError::UnexpectedEnd)?;
+        self.position += 1;
+        Ok(token)
+    }
+    // The error for the token that was just read, or for the end
+    fn unexpected(&self, position: usize) -> ParseError {
+        match self.tokens.get(position) {
+            Some(token) => ParseError::Unexpected(token.1),
+            None => ParseError::UnexpectedEnd,
+        }
+    }
+    fn eat(&mut self, symbol: char) -> bool {
+        let found = self.peek() == Some(&Token::Symbol(symbol));
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+    fn expect(&mut self, symbol: char) -> Result<(), ParseError> {
+        if self.eat(symbol) { Ok(()) } else { Err(self.unexpected(self.position)) }
+    }
+
+    fn sum(&mut self) -> Result<Expression<BigRational>, ParseError> {
+        let mut e = self.product()?;
+        loop {
+            if self.eat('+') {
+                e = e + self.product()?;
+            }
+            else if self.eat('-') {
+                e = e - self.product()?;
+            }
+            else {
+                return Ok(e);
+            }
+        }
+    }
+
+    fn product(&mut self) -> Result<Expression<BigRational>, ParseError> {
+        let mut e = self.negation()?;
+        loop {
+            if self.eat('*') {
+                e = e * self.negation()?;
+            }
+            else if self.eat('/') {
+                e = divide(e, self.negation()?)?;
+            }
+            else if self.starts_factor() {
+                e = e * self.power()?;
+            }
+            else {
+                return Ok(e);
+            }
+        }
+    }
+
+    // Whether the next token can start a factor that is multiplied without an operator
+    fn starts_factor(&self) -> bool {
+        match self.peek() {
+            Some(Token::Number(_)) | Some(Token::Name(_)) => true,
+            Some(Token::Symbol('|')) => self.abs_depth == 0,
+            Some(Token::Symbol(c)) => "(√⌊⌈".contains(*c),
+            _ => false,
+        }
+    }
+
+    fn negation(&mut self) -> Result<Expression<BigRational>, ParseError> {
+        if self.eat('-') {
+            Ok(Val(-BigRational::one()) * self.negation()?)
+        }
+        else {
+            self.power()
+        }
+    }
+
+    // Powers are right associative, so 2^3^2 is 2^9
+    fn power(&mut self) -> Result<Expression<BigRational>, ParseError> {
+        let base = self.factorial()?;
+        if self.eat('^') {
+            return Ok(base.pow(self.negation()?));
+        }
+        if let Some(Token::Superscript(exponent)) = self.peek() {
+            let exponent = exponent.clone();
+            self.position += 1;
+            return Ok(base.pow(exponent));
+        }
+        Ok(base)
+    }
+
+    fn factorial(&mut self) -> Result<Expression<BigRational>, ParseError> {
+        let mut e = self.primary()?;
+        while self.eat('!') {
+            // x!! is a double factorial, not (x!)!
+            if self.peek() == Some(&Token::Symbol('!')) {
+                return Err(ParseError::Unsupported(String::from("!!")));
+            }
+            e = e.factorial();
+        }
+        Ok(e)
+    }
+
+    fn primary(&mut self) -> Result<Expression<BigRational>, ParseError> {
+        let start = self.position;
+        Ok(match self.next()? {
+            Token::Number(n) => Val(n),
+            Token::Symbol('(') => {
+                let e = self.sum()?;
+                self.expect(')')?;
+                e
+            },
+            Token::Symbol('|') => {
+                self.abs_depth += 1;
+                let e = self.sum()?;
+                self.abs_depth -= 1;
+                self.expect('|')?;
+                e.abs()
+            },
+            Token::Symbol('⌊') => {
+                let e = self.sum()?;
+                self.expect('⌋')?;
+                e.floor()
+            },
+            Token::Symbol('⌈') => {
+                let e = self.sum()?;
+                self.expect('⌉')?;
+                ceil(e)
+            },
+            Token::Symbol('√') => self.factorial()?.pow(half()),
+            Token::Name(name) => self.name(name)?,
+            _ => return Err(self.unexpected(start)),
+        })
+    }
+
+    fn name(&mut self, name: String) -> Result<Expression<BigRational>, ParseError> {
+        Ok(match name.as_str() {
+            "x" => Independent,
+            "pi" | "Math.PI" => Pi,
+            "sqrt" | "Math.sqrt" => self.argument()?.pow(half()),
+            "ln" | "Math.log" => self.argument()?.ln(),
+            "Math.log10" => log(Val(integer(10)), self.argument()?),
+            "log" => {
+                let base = if self.eat('_') {
+                    self.primary()?
+                }
+                else if let Some(Token::Subscript(base)) = self.peek() {
+                    let base = Val(base.clone());
+                    self.position += 1;
+                    base
+                }
+                else {
+                    Val(integer(10))
+                };
+                log(base, self.argument()?)
+            },
+            "sin" | "Math.sin" => self.argument()?.sin(),
+            "cos" | "Math.cos" => self.argument()?.cos(),
+            "abs" | "Math.abs" => self.argument()?.abs(),
+            "floor" | "Math.floor" => self.argument()?.floor(),
+            "ceil" | "Math.ceil" => ceil(self.argument()?),
+            "factorial" => self.argument()?.factorial(),
+            "Math.pow" => {
+                let (base, exponent) = self.pair()?;
+                base.pow(exponent)
+            },
+            "frac" => {
+                let (numerator, denominator) = self.pair()?;
+                divide(numerator, denominator)?
+            },
+            // n!/(k!(n-k)!)
+            "C" | "binomial" | "binom" => {
+                let (n, k) = self.pair()?;
+                n.clone().factorial() / (k.clone().factorial() * (n - k).factorial())
+            },
+            _ => return Err(ParseError::Unsupported(name)),
+        })
+    }
+
+    fn argument(&mut self) -> Result<Expression<BigRational>, ParseError> {
+        Ok(self.arguments(1)?.pop().unwrap())
+    }
+
+    fn pair(&mut self) -> Result<(Expression<BigRational>, Expression<BigRational>), ParseError> {
+        let mut arguments = self.arguments(2)?;
+        let second = arguments.pop().unwrap();
+        Ok((arguments.pop().unwrap(), second))
+    }
+
+    // Reads n arguments, which are either separated by commas, as in Math.pow(2, x), or
+    // each in their own group, as in \frac{1}{2}
+    fn arguments(&mut self, n: usize) -> Result<Vec<Expression<BigRational>>, ParseError> {
+        let mut arguments = Vec::new();
+        while arguments.len() < n {
+            self.expect('(')?;
+            arguments.push(self.sum()?);
+            while self.eat(',') {
+                arguments.push(self.sum()?);
+            }
+            self.expect(')')?;
+        }
+        if arguments.len() != n {
+            return Err(self.unexpected(self.position - 1));
+        }
+        Ok(arguments)
+    }
+}
+
+fn half() -> Expression<BigRational> {
+    Val(BigRational::new(1.into(), 2.into()))
+}
+
+// log_b(a) = ln(a)/ln(b)
+fn log(base: Expression<BigRational>, argument: Expression<BigRational>) -> Expression<BigRational> {
+    argument.ln() / base.ln()
+}
+
+// ceil(u) = -floor(-u)
+fn ceil(e: Expression<BigRational>) -> Expression<BigRational> {
+    let negative = |e| Val(-BigRational::one()) * e;
+    negative(negative(e).floor())
+}
+
+impl FromStr for Expression<BigRational> {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser { tokens: tokenize(s)?, position: 0, abs_depth: 0 };
+        let e = parser.sum()?;
+        match parser.peek() {
+            Some(_) => Err(parser.unexpected(parser.position)),
+            None => Ok(e),
+        }
+    }
+}
+
+// Dividing two constants folds them into one value, which would panic for a denominator of 0
+fn divide(numerator: Expression<BigRational>, denominator: Expression<BigRational>) -> Result<Expression<BigRational>, ParseError> {
+    if let Val(d) = &denominator {
+        if d.is_zero() {
+            return Err(ParseError::DivideByZero);
+        }
+    }
+    Ok(numerator / denominator)
+}
+
+impl FromStr for Function<BigRational> {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self::from(s.parse::<Expression<BigRational>>()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        function::{Function, Expression::{self, *}},
+        polynomial::Polynomial,
+        exponential::Exponential,
+        sinusoidal::Sinusoidal,
+        logarithmic::Logarithmic,
+        error::ParseError,
+        util::{as_ratios, integer, ratio},
+    };
+    use config::fmt::{formatters, FmtAble, FmtEr};
+    use num::BigRational;
+
+    fn parse(s: &str) -> Result<Expression<BigRational>, ParseError> {
+        s.parse()
+    }
+
+    fn val(n: i32) -> Expression<BigRational> {
+        Val(integer(n))
+    }
+
+    fn x() -> Expression<BigRational> {
+        Independent
+    }
+
+    // Formats with each formatter and parses the result back, checking that the function
+    // is the same at a few points
+    fn round_trip(model: &impl FmtAble, expected: &Expression<BigRational>) {
+        fn check(f: &impl FmtEr, model: &impl FmtAble, expected: &Expression<BigRational>) {
+            let formatted = model.format(f);
+            let parsed = parse(formatted.as_str()).unwrap_or_else(|err| panic!("{}: {:?}", formatted, err));
+            for x in -2..4 {
+                assert_eq!(parsed.eval(&integer(x)), expected.eval(&integer(x)), "{} at {}", formatted, x);
+            }
+        }
+        check(&formatters::ASCII, model, expected);
+        check(&formatters::Unicode, model, expected);
+        check(&formatters::Java_JS, model, expected);
+        check(&formatters::LaTeX, model, expected);
+    }
+
+    #[test]
+    fn precedence() {
+        assert_eq!(parse("1+2*x^2"), Ok(val(1) + val(2) * x().pow(val(2))));
+        assert_eq!(parse("-x^2"), Ok(val(-1) * x().pow(val(2))));
+        assert_eq!(parse("2^3^x"), Ok(val(2).pow(val(3).pow(x()))));
+        assert_eq!(parse("x-1-2"), Ok(x() - val(1) - val(2)));
+        assert_eq!(parse("2x(x+1)"), Ok(val(2) * x() * (x() + val(1))));
+        assert_eq!(parse("(1/2)x!"), Ok(Val(ratio(1, 2)) * x().factorial()));
+    }
+
+    #[test]
+    fn functions() {
+        assert_eq!(parse("sqrt(x)+|x-1|"), Ok(x().pow(Val(ratio(1, 2))) + (x() - val(1)).abs()));
+        assert_eq!(parse("log_2(x)"), Ok(x().ln() / val(2).ln()));
+        assert_eq!(parse("floor(x/2)"), Ok((x() / val(2)).floor()));
+        assert_eq!("ceil(x/2)".parse::<Function<BigRational>>().unwrap().eval(&integer(3)), Ok(integer(2)));
+        assert_eq!(parse("C(x, 2)").unwrap().eval(&integer(5)), Ok(integer(10)));
+    }
+
+    #[test]
+    fn unicode() {
+        assert_eq!(parse("𝑥²·2ˣ"), Ok(x().pow(val(2)) * val(2).pow(x())));
+        assert_eq!(parse("𝑥⁻¹ᐟ²"), Ok(x().pow(Val(ratio(-1, 2)))));
+        assert_eq!(parse("log₂(𝑥)+√5+⌊π𝑥⌋"), Ok(x().ln() / val(2).ln() + val(5).pow(Val(ratio(1, 2))) + (Pi * x()).floor()));
+    }
+
+    #[test]
+    fn java_js() {
+        assert_eq!(parse("3*Math.pow(x, 2)+Math.abs(x)"), Ok(val(3) * x().pow(val(2)) + x().abs()));
+        assert_eq!(parse("Math.log(x)/Math.log(2)"), parse("log_2(x)"));
+        assert_eq!(parse("Math.sin(Math.PI*x)"), Ok((Pi * x()).sin()));
+    }
+
+    #[test]
+    fn latex() {
+        assert_eq!(parse(r"\frac{1}{2}x^{10}"), Ok(Val(ratio(1, 2)) * x().pow(val(10))));
+        assert_eq!(parse(r"\left|x\right|\cdot\sqrt{2}"), Ok(x().abs() * val(2).pow(Val(ratio(1, 2)))));
+        assert_eq!(parse(r"\log_{3}\left(x\right)"), parse("log_3(x)"));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(parse("x+"), Err(ParseError::UnexpectedEnd));
+        assert_eq!(parse("(x+1"), Err(ParseError::UnexpectedEnd));
+        assert_eq!(parse("x)"), Err(ParseError::Unexpected(1)));
+        assert_eq!(parse("2 $ x"), Err(ParseError::Unexpected(2)));
+        assert_eq!(parse("tan(x)"), Err(ParseError::Unsupported("tan".into())));
+        assert_eq!(parse("x!!"), Err(ParseError::Unsupported("!!".into())));
+        assert_eq!(parse("1/0"), Err(ParseError::DivideByZero));
+        assert_eq!(parse(r"\frac{1}{0}"), Err(ParseError::DivideByZero));
+        assert_eq!(parse("x/(2-2)"), Err(ParseError::DivideByZero));
+    }
+
+    #[test]
+    fn function() {
+        let parsed: Function<BigRational> = "x(x+1)".parse().unwrap();
+        assert_eq!(parsed, Function::from(x().pow(val(2)) + x()));
+    }
+
+    #[test]
+    fn formatters() {
+        let polynomial = Polynomial::from_values(&vec![ratio(-1, 2), ratio(-2, 1), ratio(-9, 2), ratio(-8, 1)], 0).unwrap();
+        round_trip(&polynomial, &Expression::from(&polynomial));
+        let exponential = Exponential::from_values(&vec![ratio(5, 1), ratio(3, 1), ratio(2, 1)]).unwrap();
+        round_trip(&exponential, &Expression::from(&exponential));
+        let derivative = Exponential::from_values(&as_ratios(vec![1, 4, 16])).unwrap().antiderivative().unwrap().derivative();
+        round_trip(&derivative, &Expression::from(&derivative));
+        let sinusoidal = Sinusoidal::from_values(&as_ratios(vec![1, 2, 1, -1, -2, -1])).unwrap();
+        round_trip(&sinusoidal, &Expression::from(&sinusoidal));
+        let points = vec![2, 3, 5, 9].into_iter().zip(0..).map(|point| (integer(point.0), integer(point.1))).collect();
+        let logarithmic = Logarithmic::from_points(&points).unwrap();
+        round_trip(&logarithmic, &Expression::from(&logarithmic));
+    }
+}
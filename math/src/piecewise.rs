@@ -0,0 +1,198 @@
+use core::ops::{Bound, RangeBounds};
+use num::BigRational;
+use config::fmt::{FmtAble, FmtEr};
+use crate::{
+    polynomial::Polynomial,
+    exponential::Exponential,
+    sinusoidal::Sinusoidal,
+    absolute_value::PiecewiseLinear,
+    function::IntervalPart,
+    predict::Predict,
+    error::{FitError, EvalError, require},
+    util::{integer, format_ratio},
+};
+use alloc::{vec::Vec, string::String};
+
+// The types of functions that a piece of a Piecewise function can be
+#[derive(Debug)]
+pub enum Piece {
+    Polynomial(Polynomial),
+    Exponential(Exponential),
+    Sinusoidal(Sinusoidal),
+}
+impl Piece {
+    // Finds a function for the values, where the first value is at x = starting. There must be
+    // more values than the function has unknowns.
+    fn from_values(values: &Vec<BigRational>, starting: i32) -> Option<Self> {
+        if let Some(polynomial) = Polynomial::from_values(values, starting).ok().filter(|p| (p.degree() as usize) + 1 < values.len()) {
+            Some(Piece::Polynomial(polynomial))
+        }
+        else if let Some(mut exponential) = Exponential::from_values(values).ok().filter(|_| values.len() > 3) {
+            exponential.shift(starting);
+            Some(Piece::Exponential(exponential))
+        }
+        else if let Ok(mut sinusoidal) = Sinusoidal::from_values(values) {
+            sinusoidal.shift(starting);
+            Some(Piece::Sinusoidal(sinusoidal))
+        }
+        else {
+            None
+        }
+    }
+}
+impl Predict for Piece {
+    fn predict(&self, x: i32) -> Result<BigRational, EvalError> {
+        match self {
+            Piece::Polynomial(p) => p.predict(x),
+            Piece::Exponential(p) => p.predict(x),
+            Piece::Sinusoidal(p) => p.predict(x),
+        }
+    }
+}
+impl FmtAble for Piece {
+    fn format(&self, f: &impl FmtEr) -> String {
+        match self {
+            Piece::Polynomial(p) => p.format(f),
+            Piece::Exponential(p) => p.format(f),
+            Piece::Sinusoidal(p) => p.format(f),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Piecewise {
+    pieces: Vec<(IntervalPart<BigRational>, Piece)>,
+}
+impl Piecewise {
+    pub fn new(pieces: Vec<(IntervalPart<BigRational>, Piece)>) -> Self {
+        Self { pieces }
+    }
+    // Splits the values into the fewest contiguous pieces that each match a function. If there
+    // is more than one way to do this, earlier pieces are made as long as possible.
+    pub fn from_values(values: &Vec<BigRational>, starting: i32) -> Result<Self, FitError> {
+        // The shortest pieces are constants with two values each
+        require(values, 4)?;
+        let len = values.len();
+        // fewest[i] is the fewest pieces that values[i..] can be split into
+        let mut fewest: Vec<Option<usize>> = vec![None; len + 1];
+        fewest[len] = Some(0);
+        for i in (0..len).rev() {
+            fewest[i] = (i + 1..=len)
+                .filter_map(|j| fewest[j].filter(|_| Piece::from_values(&values[i..j].to_vec(), starting + i as i32).is_some()))
+                .min()
+                .map(|count| count + 1);
+        }
+        // The first value can't start any split, so no piece can be found for some of the values
+        let count = fewest[0].ok_or(FitError::InconsistentPoint(0))?;
+        // A single piece is not piecewise
+        if count < 2 {
+            return Err(FitError::Singular);
+        }
+        let mut pieces = Vec::new();
+        let mut i = 0;
+        while i < len {
+            let (j, piece) = (i + 1..=len).rev()
+                .filter(|j| fewest[*j] == Some(fewest[i].unwrap() - 1))
+                .find_map(|j| Some((j, Piece::from_values(&values[i..j].to_vec(), starting + i as i32)?)))
+                .unwrap();
+            let start = if i == 0 { Bound::Unbounded } else { Bound::Included(integer(starting + i as i32)) };
+            let end = if j == len { Bound::Unbounded } else { Bound::Excluded(integer(starting + j as i32)) };
+            pieces.push((IntervalPart(start, end), piece));
+            i = j;
+        }
+        Ok(Self::new(pieces))
+    }
+    pub fn pieces(&self) -> &Vec<(IntervalPart<BigRational>, Piece)> {
+        &self.pieces
+    }
+}
+impl From<PiecewiseLinear> for Piecewise {
+    fn from(linear: PiecewiseLinear) -> Self {
+        Self::new(linear.pieces().into_iter().map(|piece| (piece.0, Piece::Polynomial(piece.1))).collect())
+    }
+}
+impl Predict for Piecewise {
+    fn predict(&self, x: i32) -> Result<BigRational, EvalError> {
+        let x_ratio = integer(x);
+        self.pieces.iter().find(|piece| piece.0.contains(&x_ratio)).ok_or(EvalError::Undefined)?.1.predict(x)
+    }
+}
+impl FmtAble for Piecewise {
+    fn format(&self, f: &impl FmtEr) -> String {
+        let cases: Vec<(String, String)> = self.pieces.iter().map(|piece| {
+            let lower = match &(piece.0).0 {
+                Bound::Included(v) => Some((format_ratio(v, f), true)),
+                Bound::Excluded(v) => Some((format_ratio(v, f), false)),
+                Bound::Unbounded => None,
+            };
+            let upper = match &(piece.0).1 {
+                Bound::Included(v) => Some((format_ratio(v, f), true)),
+                Bound::Excluded(v) => Some((format_ratio(v, f), false)),
+                Bound::Unbounded => None,
+            };
+            (
+                piece.1.format(f),
+                f.between(lower.as_ref().map(|b| (b.0.as_str(), b.1)), upper.as_ref().map(|b| (b.0.as_str(), b.1))),
+            )
+        }).collect();
+        f.cases(&cases)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Piecewise;
+    use crate::{util::as_ratios, absolute_value::PiecewiseLinear};
+    use config::fmt::{formatters, FmtAble};
+    const ASCII: formatters::ASCII = formatters::ASCII;
+
+    fn linear_then_exponential() -> Piecewise {
+        Piecewise::from_values(&as_ratios(vec![0, 1, 2, 3, 16, 32, 64, 128]), 0).unwrap()
+    }
+
+    #[test]
+    fn two_polynomials() {
+        assert_eq!(Piecewise::from_values(&as_ratios(vec![5, 5, 5, 0, 1, 2]), 0).unwrap().format(&ASCII), "{5 if x<3, x-3 if 3<=x}");
+    }
+
+    #[test]
+    fn polynomial_and_exponential() {
+        assert_eq!(linear_then_exponential().format(&ASCII), "{x if x<4, 2^x if 4<=x}");
+    }
+
+    #[test]
+    fn shifted_exponential() {
+        assert_eq!(Piecewise::from_values(&as_ratios(vec![0, 0, 0, 3, 6, 12, 24]), 0).unwrap().format(&ASCII), "{0 if x<3, 3/8*2^x if 3<=x}");
+    }
+
+    #[test]
+    fn three_pieces() {
+        assert_eq!(Piecewise::from_values(&as_ratios(vec![1, 1, 1, 2, 3, 4, 0, 0]), 1).unwrap().format(&ASCII), "{1 if x<4, x-2 if 4<=x<7, 0 if 7<=x}");
+    }
+
+    #[test]
+    fn from_piecewise_linear() {
+        let linear = PiecewiseLinear::from_values(&as_ratios(vec![0, 1, 2, 2, 2, 1, 0]), 0).unwrap();
+        assert_eq!(Piecewise::from(linear).format(&ASCII), "{x if x<2, 2 if 2<=x<4, -x+6 if 4<=x}");
+    }
+
+    #[test]
+    fn one_piece() {
+        assert!(Piecewise::from_values(&as_ratios(vec![1, 2, 3, 4]), 0).is_err());
+    }
+
+    #[test]
+    fn unicode() {
+        assert_eq!(linear_then_exponential().format(&formatters::Unicode), "{𝑥 if 𝑥<4, 2ˣ if 4≤𝑥}");
+    }
+
+    #[test]
+    fn java_js() {
+        assert_eq!(linear_then_exponential().format(&formatters::Java_JS), "x<4 ? x : Math.pow(2, x)");
+    }
+
+    #[test]
+    fn latex() {
+        assert_eq!(linear_then_exponential().format(&formatters::LaTeX), r"\begin{cases} x & x<4 \\ 2^x & 4\leq x \end{cases}");
+    }
+}
@@ -1,44 +1,259 @@
-use num::{Zero, rational::Ratio};
+use num::{BigInt, BigRational, Zero, One, Signed, Integer};
 use config::fmt::{FmtAble, FmtEr};
-use crate::ratio_field::RatioField;
-use nalgebra::{DMatrix as Matrix, DVector as Vector};
+use crate::{function::Expression, rational_fn::{RationalFunction, times_one_minus_x}, predict::Predict, error::{FitError, EvalError, require, distinct}, util::{integer, as_points, format_term, MAX_TRIAL_DIVISOR}};
 use alloc::{vec::Vec, string::{String, ToString}};
+use core::{convert::TryFrom, ops::Neg};
 
 #[derive(Debug)]
 pub struct Polynomial {
     terms: Vec<Term>,
 }
 impl Polynomial {
-    pub fn from_values(values: &Vec<Ratio<i32>>, starting: i32) -> Option<Self> {
-        let mut points = Vec::with_capacity(values.len());
-        for i in values.iter().enumerate() {
-            points.push((Ratio::from_integer(i.0 as i32 + starting), *i.1));
-        }
-        Self::from_points(&points)
+    pub fn from_values(values: &Vec<BigRational>, starting: i32) -> Result<Self, FitError> {
+        Self::from_points(&as_points(values, starting))
     }
-    pub fn from_points(points: &Vec<(Ratio<i32>, Ratio<i32>)>) -> Option<Self> {
+    pub fn from_points(points: &Vec<(BigRational, BigRational)>) -> Result<Self, FitError> {
+        require(points, 1)?;
+        distinct(points)?;
         let len = points.len();
-        let range = (0..len as i32).rev();
-        let mut x_matrix: Vec<RatioField<i32>> = Vec::with_capacity(len.pow(2));
-        let mut y_vector: Vec<RatioField<i32>> = Vec::with_capacity(len);
-        for point in points {
-            for i in range.clone() {
-                // FIXME: These numbers get really freaking big
-                x_matrix.push(point.0.pow(i).into());
+        // Newton's divided differences. Afterwards, differences[i] is the coefficient of
+        // (x-x0)(x-x1)...(x-x(i-1)) in the polynomial through all the points.
+        let mut differences: Vec<BigRational> = points.iter().map(|point| point.1.clone()).collect();
+        for j in 1..len {
+            for i in (j..len).rev() {
+                differences[i] = (&differences[i] - &differences[i - 1]) / (&points[i].0 - &points[i - j].0);
+            }
+        }
+        // Expands the Newton form with Horner's method, multiplying by x-xi and adding the
+        // next difference, starting from the innermost product
+        let mut coefficients = vec![BigRational::zero(); len];
+        for i in (0..len).rev() {
+            let x = &points[i].0;
+            for k in (1..len).rev() {
+                coefficients[k] = &coefficients[k - 1] - &coefficients[k] * x;
             }
-            y_vector.push(point.1.into());
+            coefficients[0] = &differences[i] - &coefficients[0] * x;
         }
-        //println!("{}", Matrix::from_row_slice(len, len, &x_matrix));
-        let mut terms: Vec<Term> = (Matrix::from_row_slice(len, len, &x_matrix).try_inverse()? * Vector::from_vec(y_vector))
-            .data.as_vec().iter().enumerate().filter(|e| *e.1 != RatioField::zero())
-            .map(|i| Term { coefficient: (*i.1).into(), exponent: (len - 1 - i.0) as u8 } )
-            .collect();
+        Self::from_coefficients(&coefficients)
+    }
+    // coefficients[i] is the coefficient of x^i. The exponents are stored as u8, so the degree
+    // can be at most 255, but any number of zeros can come after the last nonzero coefficient.
+    pub(crate) fn from_coefficients(coefficients: &[BigRational]) -> Result<Self, FitError> {
+        let terms = coefficients.iter().enumerate().rev().filter(|e| !e.1.is_zero())
+            .map(|e| Ok(Term { coefficient: e.1.clone(), exponent: u8::try_from(e.0).map_err(|_| FitError::Overflow)? }))
+            .collect::<Result<_, _>>()?;
+        Ok(Self::from_terms(terms))
+    }
+    // a+bx, which is never too large to store
+    pub(crate) fn linear(constant: BigRational, slope: BigRational) -> Self {
+        Self::from_terms(vec![Term { coefficient: slope, exponent: 1 }, Term { coefficient: constant, exponent: 0 }])
+    }
+    // The terms must be in order from highest exponent to lowest
+    fn from_terms(mut terms: Vec<Term>) -> Self {
+        terms.retain(|term| !term.coefficient.is_zero());
         if terms.len() == 0 {
-            terms = vec![ Term { coefficient: Ratio::zero(), exponent: 0} ]
+            terms = vec![ Term { coefficient: BigRational::zero(), exponent: 0} ]
         }
-        Some(Self {
+        Self {
             terms
-        })
+        }
+    }
+    // The opposite of from_coefficients
+    pub(crate) fn coefficients(&self) -> Vec<BigRational> {
+        let mut coefficients = vec![BigRational::zero(); self.degree() as usize + 1];
+        for term in self.terms.iter() {
+            coefficients[term.exponent as usize] = term.coefficient.clone();
+        }
+        coefficients
+    }
+    pub fn degree(&self) -> u8 {
+        self.terms[0].exponent
+    }
+    pub fn is_zero(&self) -> bool {
+        self.terms[0].coefficient.is_zero()
+    }
+    // A polynomial is defined everywhere, so this can't fail
+    pub fn eval(&self, x: &BigRational) -> BigRational {
+        self.terms.iter().fold(BigRational::zero(), |sum, term| sum + term.eval(x))
+    }
+    pub fn derivative(&self) -> Self {
+        Self::from_terms(self.terms.iter().filter(|term| term.exponent > 0)
+            .map(|term| Term { coefficient: &term.coefficient * integer(term.exponent as i32), exponent: term.exponent - 1 })
+            .collect())
+    }
+    // The antiderivative that is 0 at x = 0, or None if its degree would be too large
+    // to store
+    pub fn antiderivative(&self) -> Option<Self> {
+        let mut coefficients = vec![BigRational::zero()];
+        coefficients.extend(self.coefficients().iter().enumerate().map(|coefficient| coefficient.1 / integer(coefficient.0 as i32 + 1)));
+        Self::from_coefficients(&coefficients).ok()
+    }
+    // S(x) = p(0)+p(1)+...+p(x), found with Faulhaber's formula, or None if its degree would
+    // be too large to store
+    pub fn partial_sum(&self) -> Option<Self> {
+        let coefficients = self.coefficients();
+        let bernoulli = bernoulli_numbers(coefficients.len());
+        // Faulhaber's formula starts at 1, so p(0) is added separately
+        let mut sum = vec![BigRational::zero(); coefficients.len() + 1];
+        sum[0] = coefficients[0].clone();
+        for (m, coefficient) in coefficients.iter().enumerate() {
+            // 1^m+2^m+...+x^m is the sum of (m+1 choose j)*Bj*x^(m+1-j)/(m+1) for j from 0 to m
+            let mut binomial = BigInt::one();
+            for (j, b) in bernoulli[0..=m].iter().enumerate() {
+                sum[m + 1 - j] += coefficient * BigRational::from_integer(binomial.clone()) * b / integer(m as i32 + 1);
+                binomial = binomial * BigInt::from(m + 1 - j) / BigInt::from(j + 1);
+            }
+        }
+        Self::from_coefficients(&sum).ok()
+    }
+    // The ordinary generating function p(0)+p(1)x+p(2)x^2+..., whose denominator is
+    // (1-x)^(n+1), where n is the degree, or None if that degree would be too large to store
+    pub fn generating_function(&self) -> Option<RationalFunction> {
+        let mut denominator = vec![BigRational::one()];
+        for _ in 0..=self.degree() {
+            times_one_minus_x(&mut denominator);
+        }
+        let values: Vec<BigRational> = (0..=self.degree() as i32).map(|x| self.eval(&integer(x))).collect();
+        RationalFunction::generating_function(denominator, &values).ok()
+    }
+    // Uses the rational root theorem, so irrational roots are not found. Returns None if the
+    // first or last coefficient is too large to find all of its divisors, since some roots
+    // could be missed.
+    pub fn rational_roots(&self) -> Option<Vec<BigRational>> {
+        let mut roots = Vec::new();
+        let highest = &self.terms[0];
+        let lowest = self.terms.last().unwrap();
+        if lowest.exponent > 0 {
+            roots.push(BigRational::zero());
+        }
+        if highest.exponent == lowest.exponent {
+            return Some(roots);
+        }
+        let lcm = self.terms.iter().fold(BigInt::one(), |lcm, term| lcm.lcm(term.coefficient.denom()));
+        let constant = (&lowest.coefficient * &lcm).to_integer();
+        let leading = (&highest.coefficient * &lcm).to_integer();
+        let leading_divisors = divisors(&leading)?;
+        for p in divisors(&constant)? {
+            for q in leading_divisors.iter() {
+                for candidate in [BigRational::new(p.clone(), q.clone()), BigRational::new(-&p, q.clone())].iter() {
+                    if !roots.contains(candidate) && self.eval(candidate).is_zero() {
+                        roots.push(candidate.clone());
+                    }
+                }
+            }
+        }
+        roots.sort();
+        Some(roots)
+    }
+    // Returns the leading coefficient and every root, repeated by multiplicity, if the
+    // polynomial is a product of linear factors with rational roots
+    pub fn factor(&self) -> Option<(BigRational, Vec<BigRational>)> {
+        let (roots, mut remaining) = self.divide_rational_roots().ok()?;
+        if remaining.degree() == 0 && !self.is_zero() {
+            Some((remaining.terms.remove(0).coefficient, roots))
+        }
+        else {
+            None
+        }
+    }
+    // Divides out every rational root, returning the roots (repeated by multiplicity) and
+    // the polynomial that is left, which has no rational roots
+    pub(crate) fn divide_rational_roots(&self) -> Result<(Vec<BigRational>, Polynomial), FitError> {
+        let mut remaining = self.coefficients();
+        let mut roots = Vec::new();
+        for root in self.rational_roots().ok_or(FitError::Overflow)? {
+            while let Some(quotient) = divide_root(&remaining, &root) {
+                remaining = quotient;
+                roots.push(root.clone());
+            }
+        }
+        Ok((roots, Self::from_coefficients(&remaining)?))
+    }
+    // Formats self * term, where term is not a sum
+    pub(crate) fn format_times(&self, term: &str, f: &impl FmtEr) -> String {
+        let first = &self.terms[0];
+        if self.terms.len() > 1 {
+            f.multiply(format!("({})", self.format(f)).as_str(), term)
+        }
+        else if first.exponent == 0 {
+            format_term(&first.coefficient, term, f)
+        }
+        else {
+            let monomial = Term { coefficient: BigRational::one(), exponent: first.exponent }.format(f);
+            format_term(&first.coefficient, f.multiply(monomial.as_str(), term).as_str(), f)
+        }
+    }
+}
+
+// Divides the polynomial with the given coefficients (lowest degree first) by x-root,
+// returning None if there is a remainder
+fn divide_root(coefficients: &[BigRational], root: &BigRational) -> Option<Vec<BigRational>> {
+    if coefficients.len() < 2 {
+        return None;
+    }
+    let mut quotient = vec![BigRational::zero(); coefficients.len() - 1];
+    let mut carry = BigRational::zero();
+    for i in (1..coefficients.len()).rev() {
+        carry = &coefficients[i] + carry * root;
+        quotient[i - 1] = carry.clone();
+    }
+    if (&coefficients[0] + carry * root).is_zero() {
+        Some(quotient)
+    }
+    else {
+        None
+    }
+}
+
+// The first count Bernoulli numbers, with B1 = 1/2
+fn bernoulli_numbers(count: usize) -> Vec<BigRational> {
+    let mut numbers: Vec<BigRational> = Vec::with_capacity(count);
+    // Bm = -(the sum of (m+1 choose j)*Bj for j < m)/(m+1), which gives B1 = -1/2
+    for m in 0..count {
+        let mut binomial = BigInt::one();
+        let mut sum = BigRational::zero();
+        for (j, b) in numbers.iter().enumerate() {
+            sum += b * BigRational::from_integer(binomial.clone());
+            binomial = binomial * BigInt::from(m + 1 - j) / BigInt::from(j + 1);
+        }
+        numbers.push(if m == 0 { BigRational::one() } else { -sum / integer(m as i32 + 1) });
+    }
+    if count > 1 {
+        numbers[1] = -&numbers[1];
+    }
+    numbers
+}
+
+// Returns None if n may have a pair of divisors that are both too large to find by trial division
+fn divisors(n: &BigInt) -> Option<Vec<BigInt>> {
+    let n = n.abs();
+    let mut divisors = Vec::new();
+    let mut i = BigInt::one();
+    while i <= &n / &i {
+        if i > MAX_TRIAL_DIVISOR.into() {
+            return None;
+        }
+        if (&n % &i).is_zero() {
+            let cofactor = &n / &i;
+            divisors.push(i.clone());
+            if i != cofactor {
+                divisors.push(cofactor);
+            }
+        }
+        i += 1;
+    }
+    Some(divisors)
+}
+impl Neg for &Polynomial {
+    type Output = Polynomial;
+
+    fn neg(self) -> Polynomial {
+        Polynomial::from_terms(self.terms.iter().map(|term| Term { coefficient: -&term.coefficient, exponent: term.exponent }).collect())
+    }
+}
+impl Predict for Polynomial {
+    fn predict(&self, x: i32) -> Result<BigRational, EvalError> {
+        Ok(self.eval(&integer(x)))
     }
 }
 impl FmtAble for Polynomial {
@@ -57,51 +272,44 @@ impl FmtAble for Polynomial {
     }
 }
 
+impl From<&Polynomial> for Expression<BigRational> {
+    fn from(polynomial: &Polynomial) -> Self {
+        let mut terms = polynomial.terms.iter().map(|term| match term.exponent {
+            0 => Expression::Val(term.coefficient.clone()),
+            1 => Expression::Independent.times(&term.coefficient),
+            exponent => Expression::Independent.pow(integer(exponent)).times(&term.coefficient),
+        });
+        let first = terms.next().unwrap();
+        terms.fold(first, |sum, term| sum + term)
+    }
+}
+
 #[derive(Debug)]
 struct Term {
-    pub coefficient: Ratio<i32>,
+    pub coefficient: BigRational,
     pub exponent: u8,
 }
 impl Term {
-    fn apply(&self, value: &i32) -> Ratio<i32> {
-        self.apply_ratio(&Ratio::from_integer(*value))
-    }
-    fn apply_ratio(&self, x: &Ratio<i32>) -> Ratio<i32> {
-        self.coefficient * x.pow(self.exponent as i32)
+    fn eval(&self, x: &BigRational) -> BigRational {
+        &self.coefficient * x.pow(self.exponent as i32)
     }
 }
     
 impl FmtAble for Term {
     fn format(&self, f: &impl FmtEr) -> String {
-        let mut s = String::new();
-        if self.coefficient != num::zero() {
-            let coeff_abs = num::abs(self.coefficient);
-            if coeff_abs != num::one() || self.exponent == 0 {
-                let mut coeff_str = f.divide(coeff_abs.numer().to_string().as_str(), coeff_abs.denom().to_string().as_str());
-                if self.coefficient.denom() != &1 {
-                    coeff_str = format!("({})", coeff_str);
-                }
-                s += coeff_str.as_str();
-            }
-            let x_exp = if self.exponent == 1 { f.x().to_string() }
-                else if self.exponent > 1 { f.pow(f.x().to_string().as_str(), self.exponent.to_string().as_str()) }
-                else { String::new() };
-            if s.len() == 0 { s = x_exp }
-            else if x_exp.len() != 0 { s = f.multiply(s.as_str(), x_exp.as_str())}
-            if self.coefficient < num::zero() {
-                s = f.neg(&s);
-            }
-        }
-        s
+        let x_exp = if self.exponent == 1 { f.x().to_string() }
+            else if self.exponent > 1 { f.pow(f.x().to_string().as_str(), self.exponent.to_string().as_str()) }
+            else { String::new() };
+        format_term(&self.coefficient, x_exp.as_str(), f)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::Polynomial;
+    use crate::error::FitError;
     use config::fmt::{formatters, FmtAble};
-    use num::rational::Ratio;
-    use crate::util::as_ratios;
+    use crate::util::{as_ratios, ratio, integer};
     const ASCII: formatters::ASCII = formatters::ASCII;
 
     mod horizontal {
@@ -109,14 +317,14 @@ mod tests {
 
         #[test]
         fn zero() {
-            let zero = num::zero();
-            assert_eq!(Polynomial::from_values(&vec![zero, zero, zero], 0).unwrap().format(&ASCII), "0");
+            let zero = ratio(0, 1);
+            assert_eq!(Polynomial::from_values(&vec![zero.clone(), zero.clone(), zero], 0).unwrap().format(&ASCII), "0");
         }
 
         #[test]
         fn one() {
-            let one = num::one();
-            assert_eq!(Polynomial::from_values(&vec![one, one, one], 0).unwrap().format(&ASCII), "1");
+            let one = ratio(1, 1);
+            assert_eq!(Polynomial::from_values(&vec![one.clone(), one.clone(), one], 0).unwrap().format(&ASCII), "1");
         }
     }
     
@@ -140,7 +348,7 @@ mod tests {
 
         #[test]
         fn compressed() {
-            assert_eq!(Polynomial::from_values(&vec![num::zero(), Ratio::new(1, 2), num::one()], 0).unwrap().format(&ASCII), "(1/2)x");
+            assert_eq!(Polynomial::from_values(&vec![num::zero(), ratio(1, 2), num::one()], 0).unwrap().format(&ASCII), "(1/2)x");
         }
 
         #[test]
@@ -179,7 +387,7 @@ mod tests {
 
         #[test]
         fn compressed() {
-            assert_eq!(Polynomial::from_values(&vec![num::zero(), Ratio::new(1, 2), Ratio::from_integer(2), Ratio::new(9, 2)], 0).unwrap().format(&ASCII), "(1/2)x^2");
+            assert_eq!(Polynomial::from_values(&vec![num::zero(), ratio(1, 2), ratio(2, 1), ratio(9, 2)], 0).unwrap().format(&ASCII), "(1/2)x^2");
         }
 
         #[test]
@@ -189,12 +397,106 @@ mod tests {
 
         #[test]
         fn all() {
-            assert_eq!(Polynomial::from_values(&vec![Ratio::new(-1, 2), Ratio::from_integer(-2), Ratio::new(-9, 2), Ratio::from_integer(-8)], 0).unwrap().format(&ASCII), "-(1/2)x^2-x-(1/2)");
+            assert_eq!(Polynomial::from_values(&vec![ratio(-1, 2), ratio(-2, 1), ratio(-9, 2), ratio(-8, 1)], 0).unwrap().format(&ASCII), "-(1/2)x^2-x-(1/2)");
         }
     }
 
+    #[test]
+    fn rational_roots() {
+        assert_eq!(Polynomial::from_values(&as_ratios(vec![-2, -2, 0, 4]), 0).unwrap().rational_roots(), Some(vec![ratio(-1, 1), ratio(2, 1)]));
+    }
+
+    // The divisors of 10^13 can't all be found, so a root could be missed
+    #[test]
+    fn too_large_for_roots() {
+        let polynomial = Polynomial::from_coefficients(&[-integer(10).pow(13), integer(0), integer(1)]).unwrap();
+        assert_eq!(polynomial.rational_roots(), None);
+        assert_eq!(polynomial.factor(), None);
+    }
+
+    #[test]
+    fn eval() {
+        let polynomial = Polynomial::from_values(&as_ratios(vec![-2, -2, 0, 4]), 0).unwrap();
+        assert_eq!(polynomial.eval(&ratio(1, 2)), ratio(-9, 4));
+    }
+
+    #[test]
+    fn derivative() {
+        let polynomial = Polynomial::from_values(&as_ratios(vec![-2, -2, 0, 4]), 0).unwrap();
+        assert_eq!(polynomial.derivative().format(&ASCII), "2x-1");
+        assert_eq!(polynomial.derivative().derivative().derivative().format(&ASCII), "0");
+    }
+
+    #[test]
+    fn antiderivative() {
+        let polynomial = Polynomial::from_values(&as_ratios(vec![-2, -2, 0, 4]), 0).unwrap();
+        assert_eq!(polynomial.antiderivative().unwrap().format(&ASCII), "(1/3)x^3-(1/2)x^2-2x");
+        assert_eq!(polynomial.antiderivative().unwrap().format(&formatters::LaTeX), r"(\frac{1}{3})x^3-(\frac{1}{2})x^2-2x");
+        assert_eq!(polynomial.antiderivative().unwrap().derivative().format(&ASCII), polynomial.format(&ASCII));
+    }
+
+    #[test]
+    fn partial_sum() {
+        let sum = |values: alloc::vec::Vec<i32>| Polynomial::from_values(&as_ratios(values), 0).unwrap().partial_sum().unwrap().format(&ASCII);
+        assert_eq!(sum(vec![1, 1]), "x+1");
+        assert_eq!(sum(vec![0, 1, 2]), "(1/2)x^2+(1/2)x");
+        assert_eq!(sum(vec![0, 1, 4, 9]), "(1/3)x^3+(1/2)x^2+(1/6)x");
+        assert_eq!(sum(vec![0, 1, 8, 27, 64]), "(1/4)x^4+(1/2)x^3+(1/4)x^2");
+        // The odd numbers add up to the squares
+        assert_eq!(sum(vec![1, 3, 5]), "x^2+2x+1");
+        let values = (0..8).map(|x| integer(x).pow(6) - integer(2 * x)).collect();
+        let polynomial = Polynomial::from_values(&values, 0).unwrap();
+        let sums = values.iter().scan(ratio(0, 1), |sum, value| { *sum += value; Some(sum.clone()) });
+        assert!(sums.enumerate().all(|sum| polynomial.partial_sum().unwrap().eval(&integer(sum.0 as i32)) == sum.1));
+    }
+
+    #[test]
+    fn generating_function() {
+        let generating_function = |values: alloc::vec::Vec<i32>| Polynomial::from_values(&as_ratios(values), 0).unwrap().generating_function().unwrap().format(&ASCII);
+        assert_eq!(generating_function(vec![1, 1]), "1/(-x+1)");
+        assert_eq!(generating_function(vec![0, 1, 2]), "x/(x^2-2x+1)");
+        assert_eq!(generating_function(vec![0, 1, 4, 9]), "(x^2+x)/(-x^3+3x^2-3x+1)");
+        assert_eq!(generating_function(vec![1, 3, 5]), "(x+1)/(x^2-2x+1)");
+    }
+
+    #[test]
+    fn factor() {
+        assert_eq!(Polynomial::from_values(&as_ratios(vec![0, 1, 0, 3]), -1).unwrap().factor(), Some((ratio(1, 1), as_ratios(vec![-1, 1, 1]))));
+        assert_eq!(Polynomial::from_values(&as_ratios(vec![2, 3, 6]), 0).unwrap().factor(), None);
+    }
+
+    #[test]
+    fn fractional_roots() {
+        assert_eq!(Polynomial::from_values(&as_ratios(vec![-1, 3, 15]), 0).unwrap().rational_roots(), Some(vec![ratio(-1, 2), ratio(1, 2)]));
+    }
+
     #[test]
     fn many_intercepts() {
         assert_eq!(Polynomial::from_values(&as_ratios(vec![0, 0, 0, 6, 24]), 0).unwrap().format(&ASCII), "x^3-3x^2+2x");
     }
+
+    #[test]
+    fn errors() {
+        assert_eq!(Polynomial::from_values(&vec![], 0).unwrap_err(), FitError::TooFewPoints(1));
+        assert_eq!(Polynomial::from_points(&vec![(ratio(1, 1), ratio(1, 1)), (ratio(1, 1), ratio(2, 1))]).unwrap_err(), FitError::DuplicateX);
+        assert_eq!(Polynomial::from_coefficients(&as_ratios(vec![1; 257])).unwrap_err(), FitError::Overflow);
+        assert_eq!(Polynomial::from_coefficients(&as_ratios(vec![1; 256])).unwrap().degree(), 255);
+    }
+
+    #[test]
+    fn many_points() {
+        let values = (0..60).map(|x| integer(x * x - 3)).collect();
+        assert_eq!(Polynomial::from_values(&values, 0).unwrap().format(&ASCII), "x^2-3");
+        // Only the degree is limited, not the number of points
+        assert!(Polynomial::from_values(&as_ratios(vec![0; 300]), 0).unwrap().is_zero());
+        let line = (0..300).map(|x| integer(2 * x + 1)).collect();
+        assert_eq!(Polynomial::from_values(&line, 0).unwrap().format(&ASCII), "2x+1");
+    }
+
+    #[test]
+    fn large_values() {
+        // 13^12 doesn't fit in an i32
+        let values = (0..14).map(|x| integer(x).pow(12)).collect();
+        assert_eq!(Polynomial::from_values(&values, 0).unwrap().format(&ASCII), "x^12");
+    }
 }
\ No newline at end of file
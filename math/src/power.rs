@@ -0,0 +1,165 @@
+use num::{BigRational, Zero, One, Signed, Integer, ToPrimitive};
+use config::fmt::{FmtAble, FmtEr};
+use crate::{function::Expression, predict::Predict, error::{FitError, EvalError, require_unknowns, distinct, check, all_some}, util::{root, integer, as_points, format_ratio, format_term}};
+use alloc::{vec::Vec, string::{String, ToString}};
+
+// The largest denominator and absolute value of the exponents that are tried
+const MAX_DENOMINATOR: i32 = 4;
+const MAX_EXPONENT: i32 = 4;
+
+// a*x^p+c, where p is a rational number that isn't a non-negative integer (those are polynomials)
+#[derive(Debug)]
+pub struct Power {
+    stretch: BigRational,
+    exponent: BigRational,
+    constant: BigRational,
+}
+impl Power {
+    pub fn from_values(values: &Vec<BigRational>, starting: i32) -> Result<Self, FitError> {
+        Self::from_points(&as_points(values, starting))
+    }
+    // Tries each exponent, smallest denominator first. x^p must be rational for every point,
+    // so fractional exponents usually need x values that are perfect powers.
+    pub fn from_points(points: &Vec<(BigRational, BigRational)>) -> Result<Self, FitError> {
+        require_unknowns(points, 3)?;
+        distinct(points)?;
+        let mut error = FitError::Singular;
+        for denom in 1..=MAX_DENOMINATOR {
+            for numer in (1..=MAX_EXPONENT * denom).flat_map(|n| [n, -n].to_vec()) {
+                if numer.gcd(&denom) != 1 || denom == 1 && numer > 0 {
+                    continue;
+                }
+                let exponent = BigRational::new(numer.into(), denom.into());
+                let basis = match all_some(points.iter().map(|point| pow(&point.0, &exponent)).collect()) {
+                    Ok(basis) => basis,
+                    Err(e) => {
+                        error = error.closest(e);
+                        continue;
+                    },
+                };
+                let other = match basis.iter().position(|b| *b != basis[0]) {
+                    Some(other) => other,
+                    None => continue,
+                };
+                let stretch = (&points[other].1 - &points[0].1) / (&basis[other] - &basis[0]);
+                if stretch.is_zero() {
+                    continue;
+                }
+                let new = Self {
+                    constant: &points[0].1 - &stretch * &basis[0],
+                    stretch,
+                    exponent,
+                };
+                match check(basis.iter().zip(points.iter()).map(|e| &new.stretch * e.0 + &new.constant == e.1.1)) {
+                    Ok(()) => return Ok(new),
+                    Err(e) => error = error.closest(e),
+                }
+            }
+        }
+        Err(error)
+    }
+    pub fn exponent(&self) -> BigRational {
+        self.exponent.clone()
+    }
+}
+impl From<&Power> for Expression<BigRational> {
+    fn from(power: &Power) -> Self {
+        Expression::Independent.pow(power.exponent.clone()).times(&power.stretch).plus(&power.constant)
+    }
+}
+impl Predict for Power {
+    fn predict(&self, x: i32) -> Result<BigRational, EvalError> {
+        Expression::from(self).eval(&integer(x))
+    }
+}
+impl FmtAble for Power {
+    fn format(&self, f: &impl FmtEr) -> String {
+        let x = f.x().to_string();
+        let power = |exponent: &BigRational| if exponent.is_one() { x.clone() }
+            else { f.pow(x.as_str(), format_ratio(exponent, f).as_str()) };
+        let mut s;
+        // A negative exponent is written as a fraction, so 2x^-1 is 2/x
+        if self.exponent.is_negative() {
+            let denominator = format_term(&BigRational::from_integer(self.stretch.denom().clone()), power(&-&self.exponent).as_str(), f);
+            s = f.fraction(self.stretch.numer().abs().to_string().as_str(), denominator.as_str());
+            if self.stretch.is_negative() {
+                s = f.neg(s.as_str());
+            }
+        }
+        else {
+            s = format_term(&self.stretch, power(&self.exponent).as_str(), f);
+        }
+        if !self.constant.is_zero() {
+            s = f.add(s.as_str(), format_ratio(&self.constant, f).as_str());
+        }
+        s
+    }
+}
+
+// Returns x^p if it is rational, or None if it is irrational or undefined
+fn pow(x: &BigRational, p: &BigRational) -> Option<BigRational> {
+    if x.is_zero() {
+        return if p.is_positive() { Some(BigRational::zero()) } else { None };
+    }
+    Some(root(x, p.denom().to_u32()?)?.pow(p.numer().to_i32()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Power;
+    use crate::util::{as_ratios, ratio};
+    use config::fmt::{formatters, FmtAble};
+    use num::BigRational;
+    use alloc::vec::Vec;
+    const ASCII: formatters::ASCII = formatters::ASCII;
+
+    fn points(x: Vec<i32>, y: Vec<BigRational>) -> Vec<(BigRational, BigRational)> {
+        as_ratios(x).into_iter().zip(y).collect()
+    }
+
+    fn three_halves() -> Power {
+        Power::from_points(&points(vec![0, 1, 4, 9, 16], as_ratios(vec![0, 1, 8, 27, 64]))).unwrap()
+    }
+
+    fn reciprocal() -> Power {
+        Power::from_values(&vec![ratio(1, 1), ratio(1, 2), ratio(1, 3), ratio(1, 4)], 1).unwrap()
+    }
+
+    #[test]
+    fn reciprocal_ascii() {
+        assert_eq!(reciprocal().format(&ASCII), "1/x");
+    }
+
+    #[test]
+    fn square_root() {
+        assert_eq!(Power::from_points(&points(vec![1, 4, 9, 16], as_ratios(vec![1, 2, 3, 4]))).unwrap().format(&ASCII), "x^(1/2)");
+    }
+
+    #[test]
+    fn fractional() {
+        assert_eq!(three_halves().format(&ASCII), "x^(3/2)");
+        assert_eq!(three_halves().exponent(), ratio(3, 2));
+    }
+
+    #[test]
+    fn stretched_and_translated() {
+        let values = vec![ratio(3, 1), ratio(3, 2), ratio(11, 9), ratio(9, 8)];
+        assert_eq!(Power::from_values(&values, 1).unwrap().format(&ASCII), "2/x^2+1");
+    }
+
+    #[test]
+    fn fractional_stretch() {
+        let values = vec![ratio(-1, 2), ratio(-1, 4), ratio(-1, 6), ratio(-1, 8)];
+        assert_eq!(Power::from_values(&values, 1).unwrap().format(&ASCII), "-1/(2x)");
+    }
+
+    #[test]
+    fn cube_root() {
+        assert_eq!(Power::from_points(&points(vec![-8, -1, 1, 8], as_ratios(vec![-2, -1, 1, 2]))).unwrap().format(&ASCII), "x^(1/3)");
+    }
+
+    #[test]
+    fn polynomial() {
+        assert!(Power::from_values(&as_ratios(vec![0, 1, 4, 9]), 0).is_err());
+    }
+}
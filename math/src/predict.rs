@@ -0,0 +1,120 @@
+use num::BigRational;
+use crate::{function::{Function, Expression}, error::EvalError, util::integer};
+use alloc::boxed::Box;
+
+// A function or sequence that was found from some values, and can be used to find the values
+// that come after them. For a model found with from_values(values, 0), or a sequence like a
+// recurrence, predict(values.len()) is the next value.
+pub trait Predict {
+    // The value at x. Sequences are only defined for x >= 0.
+    fn predict(&self, x: i32) -> Result<BigRational, EvalError>;
+
+    // The values at from, from+1, from+2, ..., which ends before the first value that can't be
+    // found exactly, so it is infinite for most models
+    fn predictions(&self, from: i32) -> Box<dyn Iterator<Item = BigRational> + '_> {
+        Box::new((from..).map_while(move |x| self.predict(x).ok()))
+    }
+}
+
+impl Predict for Expression<BigRational> {
+    fn predict(&self, x: i32) -> Result<BigRational, EvalError> {
+        self.eval(&integer(x))
+    }
+}
+
+impl Predict for Function<BigRational> {
+    fn predict(&self, x: i32) -> Result<BigRational, EvalError> {
+        self.eval(&integer(x))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Predict;
+    use crate::{
+        polynomial::Polynomial,
+        exponential::Exponential,
+        recurrence::Recurrence,
+        c_finite::CFinite,
+        holonomic::Holonomic,
+        logarithmic::Logarithmic,
+        quasi_polynomial::QuasiPolynomial,
+        piecewise::Piecewise,
+        rational_fn::RationalFunction,
+        function::Function,
+        error::EvalError,
+        util::{as_ratios, integer, ratio},
+    };
+    use num::BigRational;
+    use alloc::vec::Vec;
+
+    fn next(model: &impl Predict, from: i32, count: usize) -> Vec<BigRational> {
+        model.predictions(from).take(count).collect()
+    }
+
+    #[test]
+    fn polynomial() {
+        let squares = Polynomial::from_values(&as_ratios(vec![0, 1, 4, 9]), 0).unwrap();
+        assert_eq!(next(&squares, 4, 3), as_ratios(vec![16, 25, 36]));
+        assert_eq!(squares.predict(-2), Ok(integer(4)));
+    }
+
+    #[test]
+    fn exponential() {
+        let exponential = Exponential::from_values(&as_ratios(vec![3, 5, 9, 17])).unwrap();
+        assert_eq!(next(&exponential, 4, 2), as_ratios(vec![33, 65]));
+        assert_eq!(exponential.predict(-2), Ok(ratio(3, 2)));
+    }
+
+    #[test]
+    fn recurrence() {
+        let fibonacci = Recurrence::from_values(&as_ratios(vec![1, 1, 2, 3, 5, 8])).unwrap();
+        assert_eq!(next(&fibonacci, 6, 4), as_ratios(vec![13, 21, 34, 55]));
+        assert_eq!(fibonacci.predict(1), Ok(integer(1)));
+        assert_eq!(fibonacci.predict(-1), Err(EvalError::Undefined));
+        assert_eq!(fibonacci.predictions(-1).next(), None);
+    }
+
+    #[test]
+    fn c_finite() {
+        let fibonacci = CFinite::from_values(&as_ratios(vec![0, 1, 1, 2, 3, 5, 8])).unwrap();
+        assert_eq!(next(&fibonacci, 7, 3), as_ratios(vec![13, 21, 34]));
+    }
+
+    #[test]
+    fn holonomic() {
+        let factorials = Holonomic::from_values(&as_ratios(vec![1, 1, 2, 6, 24, 120])).unwrap();
+        assert_eq!(next(&factorials, 6, 2), as_ratios(vec![720, 5040]));
+    }
+
+    #[test]
+    fn quasi_polynomial() {
+        let quarter_squares = QuasiPolynomial::from_values(&as_ratios(vec![0, 0, 1, 2, 4, 6, 9, 12]), 0).unwrap();
+        assert_eq!(next(&quarter_squares, 8, 3), as_ratios(vec![16, 20, 25]));
+    }
+
+    #[test]
+    fn piecewise() {
+        let piecewise = Piecewise::from_values(&as_ratios(vec![0, 1, 2, 3, 16, 32, 64, 128]), 0).unwrap();
+        assert_eq!(next(&piecewise, 8, 2), as_ratios(vec![256, 512]));
+        assert_eq!(piecewise.predict(-1), Ok(integer(-1)));
+    }
+
+    // The predictions stop at the first value that can't be found
+    #[test]
+    fn undefined() {
+        let points = as_ratios(vec![1, 2, 4, 8]).into_iter().zip(as_ratios(vec![0, 1, 2, 3])).collect();
+        let logarithmic = Logarithmic::from_points(&points).unwrap();
+        assert_eq!(logarithmic.predict(16), Ok(integer(4)));
+        assert_eq!(logarithmic.predict(3), Err(EvalError::Irrational));
+        assert_eq!(logarithmic.predictions(-1).next(), None);
+        let reciprocal = RationalFunction::from_values(&vec![integer(1), ratio(1, 2), ratio(1, 3), ratio(1, 4)], 1).unwrap();
+        assert_eq!(next(&reciprocal, -2, 5), vec![ratio(-1, 2), integer(-1)]);
+    }
+
+    #[test]
+    fn function() {
+        let function = Function::from_values(&as_ratios(vec![1, 2, 4, 8]), 0).unwrap();
+        assert_eq!(next(&function, 4, 2), as_ratios(vec![16, 32]));
+    }
+}
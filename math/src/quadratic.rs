@@ -0,0 +1,273 @@
+use core::{cmp::Ordering, str::FromStr, ops::Neg};
+use num::{BigInt, BigRational, Zero, One, Signed, Integer};
+use config::fmt::{FmtAble, FmtEr};
+use crate::{function::Expression, util::{format_ratio, format_term, MAX_TRIAL_DIVISOR}};
+use alloc::string::{String, ToString};
+
+// An exact number a+b*sqrt(d), where a and b are rational, and d is a squarefree integer
+// greater than 1, or 1 if b is 0. Numbers with different values of d can't be added,
+// multiplied or compared, unless one of them is rational.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Quadratic {
+    rational: BigRational,
+    irrational: BigRational,
+    radicand: BigInt,
+}
+impl Quadratic {
+    // Returns None if d is negative, since the number would not be real, or if d is too large
+    // to find its square factors
+    pub fn new(rational: BigRational, mut irrational: BigRational, mut radicand: BigInt) -> Option<Self> {
+        if radicand.is_negative() {
+            return None;
+        }
+        // Move square factors out of the square root. Each prime factor that is found is
+        // divided out of remaining, and kept in squarefree if it has an odd power.
+        let mut remaining = radicand;
+        let mut squarefree = BigInt::one();
+        let mut i: u32 = 2;
+        while BigInt::from(i) * i <= remaining {
+            if i > MAX_TRIAL_DIVISOR {
+                // What is left has no prime factors up to the maximum, so if it is less than
+                // the maximum cubed, it is a prime, a product of two primes, or a square
+                if remaining >= BigInt::from(MAX_TRIAL_DIVISOR).pow(3u32) {
+                    return None;
+                }
+                let root = remaining.sqrt();
+                if &root * &root == remaining {
+                    irrational *= BigRational::from_integer(root);
+                    remaining = BigInt::one();
+                }
+                break;
+            }
+            while (&remaining % i).is_zero() {
+                remaining /= i;
+                if (&remaining % i).is_zero() {
+                    remaining /= i;
+                    irrational *= BigRational::from_integer(i.into());
+                }
+                else {
+                    squarefree *= i;
+                }
+            }
+            i += 1;
+        }
+        radicand = squarefree * remaining;
+        Some(Self::with_radicand(rational, irrational, radicand))
+    }
+    // Like new, but the radicand must already be squarefree
+    pub(crate) fn with_radicand(rational: BigRational, mut irrational: BigRational, radicand: BigInt) -> Self {
+        if radicand.is_zero() {
+            irrational = BigRational::zero();
+        }
+        if irrational.is_zero() || radicand.is_one() {
+            Self { rational: rational + irrational * radicand, irrational: BigRational::zero(), radicand: BigInt::one() }
+        }
+        else {
+            Self { rational, irrational, radicand }
+        }
+    }
+    pub fn sqrt(r: &BigRational) -> Option<Self> {
+        // sqrt(n/m) = sqrt(nm)/m
+        Self::new(BigRational::zero(), BigRational::new(BigInt::one(), r.denom().clone()), r.numer() * r.denom())
+    }
+    pub fn is_rational(&self) -> bool {
+        self.irrational.is_zero()
+    }
+    pub fn to_ratio(&self) -> Option<BigRational> {
+        if self.is_rational() { Some(self.rational.clone()) } else { None }
+    }
+    pub fn rational_part(&self) -> &BigRational {
+        &self.rational
+    }
+    pub fn irrational_part(&self) -> &BigRational {
+        &self.irrational
+    }
+    pub fn radicand(&self) -> &BigInt {
+        &self.radicand
+    }
+    // a-b*sqrt(d)
+    pub fn conjugate(&self) -> Self {
+        Self { irrational: -&self.irrational, ..self.clone() }
+    }
+    // These return None if both numbers are irrational with different radicands
+    pub fn checked_add(&self, rhs: &Self) -> Option<Self> {
+        let radicand = self.radicand_with(rhs)?;
+        Some(Self::with_radicand(&self.rational + &rhs.rational, &self.irrational + &rhs.irrational, radicand))
+    }
+    pub fn checked_sub(&self, rhs: &Self) -> Option<Self> {
+        self.checked_add(&-rhs.clone())
+    }
+    pub fn checked_mul(&self, rhs: &Self) -> Option<Self> {
+        let radicand = self.radicand_with(rhs)?;
+        Some(Self::with_radicand(
+            &self.rational * &rhs.rational + &self.irrational * &rhs.irrational * &radicand,
+            &self.rational * &rhs.irrational + &self.irrational * &rhs.rational,
+            radicand,
+        ))
+    }
+    pub fn pow(&self, n: u32) -> Self {
+        // Every power has the same radicand as self, so the multiplication can't fail
+        (0..n).fold(Self::from(BigRational::one()), |product, _| product.checked_mul(self).unwrap())
+    }
+    pub fn is_negative(&self) -> bool {
+        self.cmp_zero() == Ordering::Less
+    }
+    fn cmp_zero(&self) -> Ordering {
+        let rational = self.rational.cmp(&BigRational::zero());
+        let irrational = self.irrational.cmp(&BigRational::zero());
+        if rational == irrational || irrational == Ordering::Equal {
+            rational
+        }
+        else if rational == Ordering::Equal {
+            irrational
+        }
+        // The parts have opposite signs, so the larger one in absolute value wins
+        else {
+            match (&self.rational * &self.rational).cmp(&(&self.irrational * &self.irrational * &self.radicand)) {
+                Ordering::Greater => rational,
+                Ordering::Less => irrational,
+                Ordering::Equal => unreachable!("sqrt(d) is irrational"),
+            }
+        }
+    }
+    fn radicand_with(&self, other: &Self) -> Option<BigInt> {
+        if self.is_rational() {
+            Some(other.radicand.clone())
+        }
+        else if other.is_rational() || self.radicand == other.radicand {
+            Some(self.radicand.clone())
+        }
+        else {
+            None
+        }
+    }
+}
+impl From<BigRational> for Quadratic {
+    fn from(r: BigRational) -> Self {
+        Self { rational: r, irrational: BigRational::zero(), radicand: BigInt::one() }
+    }
+}
+impl Neg for Quadratic {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self { rational: -self.rational, irrational: -self.irrational, radicand: self.radicand }
+    }
+}
+// Numbers with different radicands aren't compared
+impl PartialOrd for Quadratic {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.checked_sub(other)?.cmp_zero())
+    }
+}
+impl FromStr for Quadratic {
+    type Err = util::ParseNumberError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (rational, irrational, radicand) = util::parse_radical(s)?;
+        Self::new(rational, irrational, radicand).ok_or(util::ParseNumberError::TooLarge)
+    }
+}
+impl From<&Quadratic> for Expression<BigRational> {
+    fn from(number: &Quadratic) -> Self {
+        if number.is_rational() {
+            return Expression::Val(number.rational.clone());
+        }
+        Expression::Val(BigRational::from_integer(number.radicand.clone()))
+            .pow(BigRational::new(1.into(), 2.into()))
+            .times(&number.irrational)
+            .plus(&number.rational)
+    }
+}
+impl FmtAble for Quadratic {
+    // Both parts are written over a common denominator, like (1+sqrt(5))/2
+    fn format(&self, f: &impl FmtEr) -> String {
+        if self.is_rational() {
+            return format_ratio(&self.rational, f);
+        }
+        let denominator = BigRational::from_integer(self.rational.denom().lcm(self.irrational.denom()));
+        let sqrt = format_term(&(&self.irrational * &denominator), f.sqrt(self.radicand.to_string().as_str()).as_str(), f);
+        let numerator = if self.rational.is_zero() { sqrt }
+            else { f.add(format_ratio(&(&self.rational * &denominator), f).as_str(), sqrt.as_str()) };
+        if denominator.is_one() {
+            numerator
+        }
+        else if self.rational.is_zero() && self.irrational.is_negative() {
+            f.neg(f.fraction(f.neg(numerator.as_str()).as_str(), denominator.to_string().as_str()).as_str())
+        }
+        else {
+            f.fraction(numerator.as_str(), denominator.to_string().as_str())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Quadratic;
+    use config::fmt::{formatters, FmtAble};
+    use crate::util::{ratio, integer};
+    use num::BigInt;
+    const ASCII: formatters::ASCII = formatters::ASCII;
+
+    fn golden_ratio() -> Quadratic {
+        Quadratic::new(ratio(1, 2), ratio(1, 2), 5.into()).unwrap()
+    }
+
+    #[test]
+    fn square_factors() {
+        assert_eq!(Quadratic::new(ratio(1, 1), ratio(1, 1), 12.into()).unwrap(), Quadratic::new(ratio(1, 1), ratio(2, 1), 3.into()).unwrap());
+        assert!(Quadratic::new(ratio(0, 1), ratio(3, 1), 4.into()).unwrap().is_rational());
+        // The square of a prime larger than the trial divisors
+        let prime = BigInt::from(1_000_003);
+        assert_eq!(Quadratic::new(ratio(0, 1), ratio(1, 1), &prime * &prime * 2), Quadratic::new(ratio(0, 1), integer(1_000_003), 2.into()));
+        assert_eq!(Quadratic::new(ratio(0, 1), ratio(1, 1), &prime * 2).unwrap().radicand(), &(&prime * 2));
+        // Too large to tell whether it has a square factor
+        assert_eq!(Quadratic::new(ratio(0, 1), ratio(1, 1), prime.pow(3u32)), None);
+    }
+
+    #[test]
+    fn sqrt() {
+        assert_eq!(Quadratic::sqrt(&ratio(1, 2)).unwrap().format(&ASCII), "sqrt(2)/2");
+        assert_eq!(Quadratic::sqrt(&ratio(9, 4)).unwrap().to_ratio(), Some(ratio(3, 2)));
+        assert!(Quadratic::sqrt(&ratio(-1, 1)).is_none());
+    }
+
+    #[test]
+    fn arithmetic() {
+        let phi = golden_ratio();
+        assert_eq!(phi.checked_mul(&phi), phi.checked_add(&Quadratic::from(ratio(1, 1))));
+        assert_eq!(phi.conjugate().checked_mul(&phi).unwrap().to_ratio(), Some(ratio(-1, 1)));
+        assert_eq!(phi.pow(3).format(&ASCII), "2+sqrt(5)");
+    }
+
+    #[test]
+    fn different_radicands() {
+        let sqrt_2 = Quadratic::sqrt(&ratio(2, 1)).unwrap();
+        let sqrt_3 = Quadratic::sqrt(&ratio(3, 1)).unwrap();
+        assert_eq!(sqrt_2.checked_add(&sqrt_3), None);
+        assert_eq!(sqrt_2.checked_mul(&sqrt_3), None);
+        assert_eq!(sqrt_2.partial_cmp(&sqrt_3), None);
+        assert_eq!(sqrt_2.checked_mul(&sqrt_2).unwrap().to_ratio(), Some(ratio(2, 1)));
+    }
+
+    #[test]
+    fn compare() {
+        let phi = golden_ratio();
+        assert!(phi > Quadratic::from(ratio(8, 5)));
+        assert!(phi < Quadratic::from(ratio(13, 8)));
+        assert!(phi.conjugate().is_negative());
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!("(1+sqrt(5))/2".parse::<Quadratic>().unwrap(), golden_ratio());
+        assert_eq!("3+2sqrt(8)".parse::<Quadratic>().unwrap().format(&ASCII), "3+4*sqrt(2)");
+        assert_eq!("sqrt(1000000000000000000000000000057)".parse::<Quadratic>(), Err(util::ParseNumberError::TooLarge));
+    }
+
+    #[test]
+    fn format() {
+        assert_eq!(golden_ratio().format(&ASCII), "(1+sqrt(5))/2");
+        assert_eq!((-Quadratic::sqrt(&ratio(1, 5)).unwrap()).format(&ASCII), "-sqrt(5)/5");
+    }
+}
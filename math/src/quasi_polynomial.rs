@@ -0,0 +1,209 @@
+use num::{BigRational, Zero, One};
+use config::fmt::{FmtAble, FmtEr};
+use crate::{polynomial::Polynomial, function::Expression, predict::Predict, error::{FitError, EvalError, require, check}, util::{integer, as_points}};
+use alloc::{vec::Vec, string::{String, ToString}};
+
+// A function that is a different polynomial depending on the remainder of x
+// when divided by the period, such as floor(x^2/4)
+#[derive(Debug)]
+pub struct QuasiPolynomial {
+    // components[r] is the polynomial for when x mod period = r
+    components: Vec<Polynomial>,
+    // Whether all the values used to find the quasi-polynomial were integers
+    integral: bool,
+}
+impl QuasiPolynomial {
+    pub fn from_values(values: &Vec<BigRational>, starting: i32) -> Result<Self, FitError> {
+        // The shortest possible period is 2, with a constant for each remainder
+        require(values, 4)?;
+        let len = values.len();
+        let points = as_points(values, starting);
+        let mut error = FitError::Singular;
+        // Tries the fewest total coefficients first. A period of 1 would just be a polynomial.
+        for coefficients in 2..=len {
+            for period in 2..=coefficients {
+                if coefficients % period != 0 {
+                    continue;
+                }
+                let degree = coefficients / period - 1;
+                // Each polynomial needs at least one more point than it has coefficients
+                if len < period * (degree + 2) {
+                    continue;
+                }
+                // The remainder of the x value of each point when divided by the period
+                let remainder = |i: usize| (i % period + starting.rem_euclid(period as i32) as usize) % period;
+                // Each component is found from the first points with its remainder, and then
+                // checked against the rest
+                let components: Vec<Polynomial> = (0..period).map(|r| {
+                    let first = points.iter().enumerate()
+                        .filter(|e| remainder(e.0) == r)
+                        .map(|e| e.1.clone())
+                        .take(degree + 1)
+                        .collect();
+                    Polynomial::from_points(&first)
+                }).collect::<Result<_, _>>()?;
+                if components.iter().all(|c| c.coefficients() == components[0].coefficients()) {
+                    continue;
+                }
+                match check(points.iter().enumerate().map(|e| components[remainder(e.0)].eval(&e.1.0) == e.1.1)) {
+                    Ok(()) => return Ok(Self {
+                        components,
+                        integral: values.iter().all(|v| v.is_integer()),
+                    }),
+                    Err(e) => error = error.closest(e),
+                }
+            }
+        }
+        Err(error)
+    }
+    pub fn period(&self) -> usize {
+        self.components.len()
+    }
+    // If every component has the same coefficients except for the constant term, and the
+    // constant terms are all within 1 of each other, then floor(p(x)) is the same function,
+    // where p is a component with the largest constant term
+    pub fn floor_form(&self) -> Option<Rounded> {
+        self.rounded_form().map(|(_, max)| Rounded::Floor(max))
+    }
+    // Like floor_form, but ceil(p(x)), where p has the smallest constant term
+    pub fn ceil_form(&self) -> Option<Rounded> {
+        self.rounded_form().map(|(min, _)| Rounded::Ceil(min))
+    }
+    fn rounded_form(&self) -> Option<(Polynomial, Polynomial)> {
+        if !self.integral {
+            return None;
+        }
+        let mut coefficients: Vec<Vec<BigRational>> = self.components.iter().map(|c| c.coefficients()).collect();
+        let len = coefficients.iter().map(|c| c.len()).max().unwrap();
+        for c in coefficients.iter_mut() {
+            c.resize(len, BigRational::zero());
+        }
+        if coefficients.iter().any(|c| c[1..] != coefficients[0][1..]) {
+            return None;
+        }
+        let min = coefficients.iter().min_by_key(|c| &c[0]).unwrap();
+        let max = coefficients.iter().max_by_key(|c| &c[0]).unwrap();
+        if &max[0] - &min[0] >= BigRational::one() {
+            return None;
+        }
+        Some((Polynomial::from_coefficients(min).ok()?, Polynomial::from_coefficients(max).ok()?))
+    }
+}
+impl Predict for QuasiPolynomial {
+    fn predict(&self, x: i32) -> Result<BigRational, EvalError> {
+        Ok(self.components[x.rem_euclid(self.period() as i32) as usize].eval(&integer(x)))
+    }
+}
+impl FmtAble for QuasiPolynomial {
+    fn format(&self, f: &impl FmtEr) -> String {
+        match self.floor_form() {
+            Some(floor) => floor.format(f),
+            None => {
+                let x = f.x().to_string();
+                let period = self.period().to_string();
+                let cases: Vec<(String, String)> = self.components.iter().enumerate()
+                    .map(|c| (c.1.format(f), f.congruent(x.as_str(), c.0.to_string().as_str(), period.as_str())))
+                    .collect();
+                f.cases(&cases)
+            },
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Rounded {
+    Floor(Polynomial),
+    Ceil(Polynomial),
+}
+// ceil(p(x)) is -floor(-p(x))
+impl From<&Rounded> for Expression<BigRational> {
+    fn from(rounded: &Rounded) -> Self {
+        let minus_one = -BigRational::one();
+        match rounded {
+            Rounded::Floor(p) => Expression::from(p).floor(),
+            Rounded::Ceil(p) => Expression::from(p).times(&minus_one).floor().times(&minus_one),
+        }
+    }
+}
+impl Predict for Rounded {
+    fn predict(&self, x: i32) -> Result<BigRational, EvalError> {
+        Expression::from(self).eval(&integer(x))
+    }
+}
+impl FmtAble for Rounded {
+    fn format(&self, f: &impl FmtEr) -> String {
+        match self {
+            Rounded::Floor(p) => f.floor(p.format(f).as_str()),
+            Rounded::Ceil(p) => f.ceil(p.format(f).as_str()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::QuasiPolynomial;
+    use crate::util::as_ratios;
+    use config::fmt::{formatters, FmtAble};
+    const ASCII: formatters::ASCII = formatters::ASCII;
+
+    fn repeated() -> QuasiPolynomial {
+        QuasiPolynomial::from_values(&as_ratios(vec![1, 1, 2, 2, 3, 3]), 0).unwrap()
+    }
+
+    fn quarter_squares() -> QuasiPolynomial {
+        QuasiPolynomial::from_values(&as_ratios(vec![0, 0, 1, 2, 4, 6, 9, 12]), 0).unwrap()
+    }
+
+    fn alternating_slope() -> QuasiPolynomial {
+        QuasiPolynomial::from_values(&as_ratios(vec![0, 2, 2, 6, 4, 10, 6, 14]), 0).unwrap()
+    }
+
+    #[test]
+    fn floor() {
+        assert_eq!(repeated().floor_form().unwrap().format(&ASCII), "floor((1/2)x+1)");
+    }
+
+    #[test]
+    fn ceil() {
+        assert_eq!(repeated().ceil_form().unwrap().format(&ASCII), "ceil((1/2)x+(1/2))");
+    }
+
+    #[test]
+    fn quadratic() {
+        assert_eq!(quarter_squares().format(&ASCII), "floor((1/4)x^2)");
+    }
+
+    #[test]
+    fn period() {
+        assert_eq!(quarter_squares().period(), 2);
+    }
+
+    #[test]
+    fn cases() {
+        assert!(alternating_slope().floor_form().is_none());
+        assert_eq!(alternating_slope().format(&ASCII), "{x if x mod 2 = 0, 2x if x mod 2 = 1}");
+    }
+
+    #[test]
+    fn polynomial() {
+        assert!(QuasiPolynomial::from_values(&as_ratios(vec![1, 2, 3, 4, 5, 6]), 0).is_err());
+    }
+
+    #[test]
+    fn unicode() {
+        assert_eq!(quarter_squares().format(&formatters::Unicode), "⌊(1/4)𝑥²⌋");
+        assert_eq!(alternating_slope().format(&formatters::Unicode), "{𝑥 if 𝑥≡0 (mod 2), 2𝑥 if 𝑥≡1 (mod 2)}");
+    }
+
+    #[test]
+    fn java_js() {
+        assert_eq!(repeated().format(&formatters::Java_JS), "Math.floor((1.0/2)*x+1)");
+        assert_eq!(alternating_slope().format(&formatters::Java_JS), "Math.floorMod(x, 2) == 0 ? x : 2*x");
+    }
+
+    #[test]
+    fn latex() {
+        assert_eq!(quarter_squares().format(&formatters::LaTeX), r"\left\lfloor (\frac{1}{4})x^2\right\rfloor");
+        assert_eq!(alternating_slope().format(&formatters::LaTeX), r"\begin{cases} x & x\equiv 0\pmod{2} \\ 2x & x\equiv 1\pmod{2} \end{cases}");
+    }
+}
@@ -0,0 +1,149 @@
+use num::{BigInt, BigRational, Zero, One, Signed};
+use config::fmt::{FmtAble, FmtEr};
+use crate::{polynomial::Polynomial, quadratic::Quadratic, function::Expression, predict::Predict, error::{FitError, EvalError, require_unknowns, distinct, check, all_some}, linear_algebra::{Matrix, Vector}, util::{integer, as_points, format_ratio, format_term}};
+use alloc::{vec::Vec, string::String};
+
+// a*sqrt(c(x-h))+k, where c is a squarefree integer, and may be negative to reflect the
+// function horizontally
+#[derive(Debug)]
+pub struct Radical {
+    stretch: BigRational,
+    scale: BigInt,
+    shift: BigRational,
+    constant: BigRational,
+}
+impl Radical {
+    pub fn from_values(values: &Vec<Quadratic>, starting: i32) -> Result<Self, FitError> {
+        Self::from_points(&as_points(values, starting))
+    }
+    // If y = a*sqrt(c(x-h))+k, then (y-k)^2 = a^2*c*x-a^2*c*h is linear in x. The values may
+    // have different radicands, but the rational part of every irrational value must be k.
+    pub fn from_points(points: &Vec<(BigRational, Quadratic)>) -> Result<Self, FitError> {
+        // a and c together only count as one unknown
+        require_unknowns(points, 3)?;
+        distinct(points)?;
+        let constant = match points.iter().find(|point| !point.1.is_rational()) {
+            Some(point) => point.1.rational_part().clone(),
+            // y^2 = Ax+2ky+C, which is linear in the unknowns A, 2k and C
+            None => {
+                let matrix = points[0..3].iter().map(|point| vec![point.0.clone(), point.1.to_ratio().unwrap(), BigRational::one()]).collect();
+                let vector = points[0..3].iter().map(|point| point.1.to_ratio().unwrap().pow(2)).collect();
+                Matrix::from_rows(matrix).solve(&Vector::new(vector)).ok_or(FitError::Singular)?[1].clone() / integer(2)
+            },
+        };
+        let k = Quadratic::from(constant.clone());
+        let squares = all_some(points.iter().map(|point| {
+            let difference = point.1.checked_sub(&k)?;
+            difference.checked_mul(&difference)?.to_ratio()
+        }).collect())?;
+        let slope = (&squares[1] - &squares[0]) / (&points[1].0 - &points[0].0);
+        if slope.is_zero() {
+            return Err(FitError::Singular);
+        }
+        let shift = &points[0].0 - &squares[0] / &slope;
+        // |A| = a^2*|c|, so the exact square root of |A| gives both a and c
+        let root = Quadratic::sqrt(&slope.abs()).ok_or(FitError::Overflow)?;
+        let (mut stretch, mut scale) = if root.is_rational() { (root.rational_part().clone(), BigInt::one()) }
+            else { (root.irrational_part().clone(), root.radicand().clone()) };
+        if slope.is_negative() {
+            scale = -scale;
+        }
+        // The slope isn't 0, so some point isn't on the line y = k
+        if points.iter().find(|point| point.1 != k).unwrap().1.checked_sub(&k).ok_or(FitError::NoClosedForm)?.is_negative() {
+            stretch = -stretch;
+        }
+        let new = Self { stretch, scale, shift, constant };
+        check(points.iter().map(|point| new.apply(&point.0).as_ref() == Some(&point.1)))?;
+        Ok(new)
+    }
+    // Returns None if x is outside of the domain, or the value under the square root is too
+    // large to simplify
+    pub fn apply(&self, x: &BigRational) -> Option<Quadratic> {
+        let root = Quadratic::sqrt(&((x - &self.shift) * &self.scale))?;
+        root.checked_mul(&Quadratic::from(self.stretch.clone()))?.checked_add(&Quadratic::from(self.constant.clone()))
+    }
+}
+impl From<&Radical> for Expression<BigRational> {
+    fn from(radical: &Radical) -> Self {
+        let scale = BigRational::from_integer(radical.scale.clone());
+        Expression::from(&Polynomial::linear(-&radical.shift * &scale, scale))
+            .pow(BigRational::new(1.into(), 2.into()))
+            .times(&radical.stretch)
+            .plus(&radical.constant)
+    }
+}
+impl Predict for Radical {
+    fn predict(&self, x: i32) -> Result<BigRational, EvalError> {
+        self.apply(&integer(x)).ok_or(EvalError::Undefined)?.to_ratio().ok_or(EvalError::Irrational)
+    }
+}
+impl FmtAble for Radical {
+    fn format(&self, f: &impl FmtEr) -> String {
+        let scale = BigRational::from_integer(self.scale.clone());
+        let inner = Polynomial::linear(-&self.shift * &scale, scale).format(f);
+        let mut s = format_term(&self.stretch, f.sqrt(inner.as_str()).as_str(), f);
+        if !self.constant.is_zero() {
+            s = f.add(s.as_str(), format_ratio(&self.constant, f).as_str());
+        }
+        s
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Radical;
+    use crate::{quadratic::Quadratic, util::{as_ratios, ratio}};
+    use config::fmt::{formatters, FmtAble};
+    use num::BigRational;
+    use alloc::vec::Vec;
+    const ASCII: formatters::ASCII = formatters::ASCII;
+
+    fn points(x: Vec<i32>, y: Vec<i32>) -> Vec<(BigRational, Quadratic)> {
+        as_ratios(x).into_iter().zip(as_ratios(y).into_iter().map(Quadratic::from)).collect()
+    }
+
+    fn parse(values: &[&str]) -> Vec<Quadratic> {
+        values.iter().map(|value| value.parse().unwrap()).collect()
+    }
+
+    fn parent() -> Radical {
+        Radical::from_points(&points(vec![1, 4, 9, 16], vec![1, 2, 3, 4])).unwrap()
+    }
+
+    #[test]
+    fn rational_values() {
+        assert_eq!(parent().format(&ASCII), "sqrt(x)");
+    }
+
+    #[test]
+    fn irrational_values() {
+        assert_eq!(Radical::from_values(&parse(&["0", "1", "sqrt(2)", "sqrt(3)", "2"]), 0).unwrap().format(&ASCII), "sqrt(x)");
+    }
+
+    #[test]
+    fn stretched_and_translated() {
+        let values = parse(&["1", "3", "1+2sqrt(2)", "1+2sqrt(3)", "5"]);
+        assert_eq!(Radical::from_values(&values, -1).unwrap().format(&ASCII), "2*sqrt(x+1)+1");
+    }
+
+    #[test]
+    fn squarefree_scale() {
+        assert_eq!(Radical::from_values(&parse(&["0", "sqrt(2)", "2", "sqrt(6)"]), 0).unwrap().format(&ASCII), "sqrt(2x)");
+    }
+
+    #[test]
+    fn reflected() {
+        assert_eq!(Radical::from_points(&points(vec![4, 3, 0, -5], vec![0, -1, -2, -3])).unwrap().format(&ASCII), "-sqrt(-x+4)");
+    }
+
+    #[test]
+    fn linear() {
+        assert!(Radical::from_points(&points(vec![1, 2, 3, 4], vec![1, 2, 3, 4])).is_err());
+    }
+
+    #[test]
+    fn symbolic() {
+        assert_eq!(parent().apply(&ratio(8, 1)).unwrap().format(&ASCII), "2*sqrt(2)");
+        assert!(parent().apply(&ratio(-1, 1)).is_none());
+    }
+}
@@ -1,18 +1,13 @@
 use core::{
-    mem, fmt, num::ParseIntError, 
-    ops::{Add, Sub, Mul, Div, Rem, Neg, AddAssign, SubAssign, MulAssign, DivAssign, RemAssign}, 
-    convert::{From, TryInto, Into}, 
+    fmt,
+    ops::{Add, Sub, Mul, Div, Rem, Neg, AddAssign, SubAssign, MulAssign, DivAssign, RemAssign},
+    convert::{From, Into, TryFrom},
 };
-use config::output::Output;
-use num::{Bounded, FromPrimitive, Integer, Num, NumCast, One, Signed, Zero, pow::Pow, rational::Ratio, traits::NumAssign};
-use nalgebra::{ComplexField, Field, RealField};
-use approx::{UlpsEq, RelativeEq, AbsDiffEq};
-use simba::{simd::SimdValue, scalar::{SubsetOf, SupersetOf}};
+use num::{BigInt, BigRational, Bounded, FromPrimitive, Integer, Num, One, Signed, Zero, integer::Roots, rational::Ratio, traits::NumAssign};
 use paste::paste;
-use alloc::borrow::ToOwned;
 
-// Some of the trait implementations in this code are derived from the
-// source code of the cates simba and approx
+// RatioField is always exact. Operations whose result might not be rational, such as
+// sqrt, return None instead of falling back to an f64 approximation.
 
 macro_rules! inherit {
     (static $($name:ident($($arg_name:ident: $arg_ty:ty),*)),* $(; $($next:tt)*)?) => {
@@ -31,22 +26,6 @@ macro_rules! inherit {
         )*
         $(inherit!($($next)*);)?
     };
-    (static use f64 $($name:ident($($arg_name:ident: $arg_ty:ty),*)),* $(; $($next:tt)*)?) => {
-        $(
-            fn $name($($arg_name: $arg_ty),*) -> Self {
-                RatioField::new(Ratio::approximate_float(f64::$name($($arg_name),*)).unwrap())
-            }
-        )*
-        $(inherit!($($next)*);)?
-    };
-    (use f64 $($name:ident($($arg_name:ident: $arg_ty:ty $(as $cast:ty)?),*)),* $(; $($next:tt)*)?) => {
-        $(
-            fn $name(self, $($arg_name: $arg_ty),*) -> Self {
-                RatioField::new(Ratio::approximate_float(Into::<f64>::into(self).$name($($(Into::<$cast>::into)?($arg_name)),*)).unwrap())
-            }
-        )*
-        $(inherit!($($next)*);)?
-    };
     ($($name:ident($($arg_name:ident: Self),*)),* $(; $($next:tt)*)?) => {
         $(
             fn $name(self, $($arg_name: Self),*) -> Self {
@@ -134,6 +113,17 @@ impl<T: Clone + Integer> RatioField<T> {
     }
 }
 
+impl<T: Clone + Integer + Roots> RatioField<T> {
+    // Returns None unless both the numerator and denominator are perfect squares
+    pub fn sqrt(&self) -> Option<Self> {
+        if self.ratio < Ratio::zero() {
+            return None;
+        }
+        let int_sqrt = |i: &T| Some(i.sqrt()).filter(|root| &(root.clone() * root.clone()) == i);
+        Some(Self::new(Ratio::new(int_sqrt(self.ratio.numer())?, int_sqrt(self.ratio.denom())?)))
+    }
+}
+
 impl<T: Clone + Integer> From<Ratio<T>> for RatioField<T> {
     fn from(ratio: Ratio<T>) -> Self {
         Self { ratio }
@@ -165,71 +155,21 @@ impl<T: Integer + Clone> Into<f64> for RatioField<T> {
     }
 }
 
+// Adds up the binary digits of n, starting with the least significant one
 fn to_f64<T: Integer + Clone>(mut n: T) -> f64 {
-    let mut digit = T::one();
-    let mut digit_f = 1.0;
+    let negative = n < T::zero();
     let two = T::one() + T::one();
-    while digit <= n {
-        digit = digit * two.clone();
-        digit_f *= 2.0;
-    }
-    digit = digit / two.clone();
-    digit_f /= 2.0;
+    let mut digit_f = 1.0;
     let mut float = 0.0;
-    while n > T::zero() {
-        if n.is_multiple_of(&digit) {
-            n = n - digit.clone();
+    while !n.is_zero() {
+        let (quotient, remainder) = n.div_rem(&two);
+        if !remainder.is_zero() {
             float += digit_f;
-            debug_assert!(n < digit.clone());
         }
-        digit = digit / two.clone();
-        digit_f /= 2.0;
-    }
-    float
-}
-
-impl<T> RealField for RatioField<T>
-    where T: Integer + Clone + Signed + Bounded + fmt::Debug + Copy + Sync + 
-        NumAssign + Send + NumCast + fmt::Display + Pow<u32, Output = T> + 'static + FromPrimitive
-{
-    fn is_sign_positive(self) -> bool {
-        self.is_positive()
-    }
-
-    fn is_sign_negative(self) -> bool {
-        self.is_negative()
-    }
-
-    fn copysign(self, to: Self) -> Self {
-        if self.ratio.signum() == to.ratio.signum() {
-            to
-        }
-        else {
-            RatioField::new(-to.ratio)
-        }
-    }
-
-    fn clamp(self, min: Self, max: Self) -> Self {
-        if self.ratio > max.ratio {
-            max
-        }
-        else if self.ratio < min.ratio {
-            min
-        }
-        else {
-            self
-        }
-    }
-
-    fn atan2(self, other: Self) -> Self {
-        RatioField::new(Ratio::approximate_float(Into::<f64>::into(self).atan2(other.into())).unwrap())
-    }
-
-    inherit! {
-        max(other: Self), min(other: Self);
-        static use f64 pi(), two_pi(), frac_pi_2(), frac_pi_3(), frac_pi_4(), frac_pi_6(), frac_pi_8(),
-            frac_1_pi(), frac_2_pi(), frac_2_sqrt_pi(), e(), log2_e(), log10_e(), ln_2(), ln_10()
+        n = quotient;
+        digit_f *= 2.0;
     }
+    if negative { -float } else { float }
 }
 
 impl<T: Integer + Clone + Bounded> Bounded for RatioField<T> {
@@ -238,219 +178,36 @@ impl<T: Integer + Clone + Bounded> Bounded for RatioField<T> {
     }
 }
 
-impl<T: Integer + Clone + Signed> Signed for RatioField<T> {
+impl<T: Integer + Clone + Signed + TryFrom<BigInt>> Signed for RatioField<T> {
     inherit! {
         abs(), abs_sub(other: &Self), signum();
         is_positive(), is_negative() -> bool;
     }
 }
 
-impl<T: Integer + Clone> Num for RatioField<T> {
+impl<T: Integer + Clone + TryFrom<BigInt>> Num for RatioField<T> {
     type FromStrRadixErr = FromStrErr;
 
     fn from_str_radix(s: &str, radix: u32) -> Result<Self, Self::FromStrRadixErr> {
         if radix != 10 {
             return Err(FromStrErr::UnsupportedRadix)
         }
-        else {
-            //Ok(RatioField::new(util::parse(s)?))
-            todo!()
+        let parsed = util::parse(s)?;
+        let (numer, denom) = parsed.into();
+        match (T::try_from(numer), T::try_from(denom)) {
+            (Ok(numer), Ok(denom)) => Ok(RatioField::new(Ratio::new(numer, denom))),
+            _ => Err(FromStrErr::Overflow),
         }
     }
 }
 
-impl<T> ComplexField for RatioField<T>
-    where T: Integer + Clone + Signed + Bounded + fmt::Debug + Copy + Sync + NumAssign + 
-        Send + NumCast + fmt::Display + 'static + Pow<u32, Output = T> + FromPrimitive
-{
-    type RealField = Self;
-
-    fn from_real(re: Self) -> Self {
-        re
-    }
-
-    fn real(self) -> Self::RealField {
-        self
-    }
-
-    fn imaginary(self) -> Self::RealField {
-        Self::zero()
-    }
-
-    fn modulus(self) -> Self::RealField {
-        Signed::abs(&self)
-    }
-
-    fn modulus_squared(self) -> Self::RealField {
-        self * self
-    }
-
-    fn norm1(self) -> Self::RealField {
-        Signed::abs(&self)
-    }
-
-    fn scale(self, factor: Self::RealField) -> Self {
-        self * factor
-    }
-
-    fn unscale(self, factor: Self::RealField) -> Self {
-        self / factor
-    }
-
-    fn mul_add(self, a: Self, b: Self) -> Self {
-        self * a + b
-    }
-
-    fn hypot(self, o: Self) -> Self::RealField {
-        (self.conjugate() * self + o.conjugate() * o).sqrt()
-    }
-
-    fn conjugate(self) -> Self {
-        self
-    }
-
-    fn sin_cos(self) -> (Self, Self) {
-        (self.sin(), self.cos())
-    }
-
-    fn powi(self, n: i32) -> Self {
-        let parts: (T, T) = self.ratio.into();
-        //RatioField::new(Ratio::new(parts.0.pow(n), parts.1.pow(n)))
-        //todo!()
-        Self::new(self.ratio.pow(n))
-    }
-
-    fn is_finite(&self) -> bool {
-        true
-    }
-
-    fn try_sqrt(self) -> Option<Self> {
-        Some(Self::new(Ratio::approximate_float(Into::<f64>::into(self).try_sqrt()?)?))
-    }
-
-    inherit! {
-        floor(), ceil(), round(), trunc(), fract(), abs(), recip();
-        use f64 sin(), cos(), tan(), asin(), acos(), atan(),
-            sinh(), cosh(), tanh(), asinh(), acosh(), atanh(),
-            log(base: Self::RealField as f64), log2(), log10(), ln(),
-            ln_1p(), sqrt(), exp(), exp2(), exp_m1(), 
-            powf(n: Self::RealField as f64), powc(n: Self as f64), cbrt(),
-            argument()
-    }
-}
-
-impl<T: Integer + Clone + Signed + NumAssign> Field for RatioField<T> { }
-
-impl<T: Integer + Clone + Signed + Bounded> RelativeEq for RatioField<T> {
-    fn default_max_relative() -> Self {
-        Self::default_epsilon()
-    }
-
-    fn relative_eq(&self, other: &Self, epsilon: Self, max_relative: Self) -> bool {
-        if self == other {
-            return true;
-        }
-
-        let abs_diff = (self.clone() - other.clone()).abs();
-
-        // For when the numbers are really close together
-        if abs_diff <= epsilon {
-            return true;
-        }
-
-        let abs_self = self.abs();
-        let abs_other = Signed::abs(other);
-
-        let largest = if abs_other > abs_self {
-            abs_other
-        } else {
-            abs_self
-        };
-
-        // Use a relative difference comparison
-        abs_diff <= largest * max_relative
-    }
-}
-
-impl<T: Integer + Clone + Signed + Bounded> UlpsEq for RatioField<T> {
-    fn default_max_ulps() -> u32 {
-        4
-    }
-
-    fn ulps_eq(&self, other: &Self, epsilon: Self, max_ulps: u32) -> bool {
-        if Self::abs_diff_eq(self, other, epsilon) {
-            return true;
-        }
-
-        if self.signum() != other.signum() {
-            return false;
-        }
-
-        let int_self: i64 = unsafe { mem::transmute(Into::<f64>::into(self.clone())) };
-        let int_other: i64 = unsafe { mem::transmute(Into::<f64>::into(other.clone())) };
-
-        (int_self - int_other).abs() < max_ulps as i64
-    }
-}
-
-impl<T: Integer + Clone> SimdValue for RatioField<T> {
-    type Element = Self;
-    type SimdBool = bool;
-
-    fn lanes() -> usize {
-        1
-    }
-
-    fn splat(val: Self::Element) -> Self {
-        val
-    }
-
-    fn extract(&self, _: usize) -> Self::Element {
-        self.to_owned()
-    }
-
-    unsafe fn extract_unchecked(&self, _: usize) -> Self::Element {
-        self.to_owned()
-    }
-
-    fn replace(&mut self, _: usize, val: Self::Element) {
-        *self = val
-    }
-
-    unsafe fn replace_unchecked(&mut self, _: usize, val: Self::Element) {
-        *self = val
-    }
-
-    fn select(self, cond: Self::SimdBool, other: Self) -> Self {
-        if cond {
-            self
-        } else {
-            other
-        }
-    }
-}
-
-impl<T> AbsDiffEq for RatioField<T>
-    where T: Clone + Integer + Bounded + Signed
-{
-    type Epsilon = Self;
-
-    fn default_epsilon() -> Self::Epsilon {
-        Self::new(Ratio::new(T::one(), T::max_value()))
-    }
-
-    fn abs_diff_eq(&self, other: &Self, epsilon: Self) -> bool {
-        (self.clone() - other.clone()).abs() <= epsilon
-    }
-}
-
 impl<T: Integer + Clone + fmt::Display> fmt::Display for RatioField<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.ratio)
     }
 }
 
-impl<T: Integer + Clone + FromPrimitive + Bounded + NumCast + Signed> FromPrimitive for RatioField<T> {
+impl<T: Integer + Clone + FromPrimitive + TryFrom<BigInt>> FromPrimitive for RatioField<T> {
     fn from_i64(n: i64) -> Option<Self> {
         Some(Self::new(Ratio::from_integer(T::from_i64(n)?)))
     }
@@ -459,51 +216,64 @@ impl<T: Integer + Clone + FromPrimitive + Bounded + NumCast + Signed> FromPrimit
         Some(Self::new(Ratio::from_integer(T::from_u64(n)?)))
     }
     
+    // Every finite float is exactly equal to some fraction, but it might not fit in T
     fn from_f64(n: f64) -> Option<Self> {
-        Some(Self::new(Ratio::approximate_float(n)?))
+        let (numer, denom) = BigRational::from_float(n)?.into();
+        Some(Self::new(Ratio::new(T::try_from(numer).ok()?, T::try_from(denom).ok()?)))
     }
 }
 
-impl<T: Integer + Clone> SubsetOf<Self> for RatioField<T> {
-    fn to_superset(&self) -> Self {
-        self.to_owned()
-    }
-
-    fn from_superset_unchecked(element: &Self) -> Self {
-        element.to_owned()
-    }
+#[derive(Debug)]
+pub enum FromStrErr {
+    Parse(util::ParseNumberError),
+    // The numerator or denominator doesn't fit in T
+    Overflow,
+    UnsupportedRadix
+}
 
-    fn is_in_subset(_: &Self) -> bool {
-        true
+impl From<util::ParseNumberError> for FromStrErr {
+    fn from(err: util::ParseNumberError) -> Self {
+        Self::Parse(err)
     }
 }
+#[cfg(test)]
+mod tests {
+    use super::{RatioField, FromStrErr};
+    use num::{BigInt, FromPrimitive, Num, rational::Ratio};
+    use util::ParseNumberError;
 
-// Mathematically, this is not true. f64 represents real
-// numbers, while Ratio represents rational numbers.
-// ℝ ⊅ ℚ, but I have to pretend it does for this to
-// work, so whatever
-impl<T: Integer + Clone + Bounded + NumCast + Signed> SupersetOf<f64> for RatioField<T> {
-    fn is_in_subset(&self) -> bool {
-        true
+    fn field(numer: i32, denom: i32) -> RatioField<i32> {
+        RatioField::new(Ratio::new(numer, denom))
     }
 
-    fn to_subset_unchecked(&self) -> f64 {
-        self.to_owned().into()
+    #[test]
+    fn perfect_square_sqrt() {
+        assert_eq!(field(9, 4).sqrt(), Some(field(3, 2)));
     }
 
-    fn from_subset(element: &f64) -> Self {
-        RatioField::new(Ratio::approximate_float(*element).unwrap())
+    #[test]
+    fn irrational_sqrt() {
+        assert_eq!(field(2, 1).sqrt(), None);
+        assert_eq!(field(1, 2).sqrt(), None);
+        assert_eq!(field(-4, 1).sqrt(), None);
     }
-}
 
-pub enum FromStrErr {
-    ParseIntError(ParseIntError),
-    InvalidNumber,
-    UnsupportedRadix
-}
+    #[test]
+    fn from_str_radix() {
+        assert_eq!(RatioField::<i32>::from_str_radix("1 1/2", 10).unwrap(), field(3, 2));
+        assert_eq!(RatioField::<BigInt>::from_str_radix("-0.25", 10).unwrap(), RatioField::new(Ratio::new((-1).into(), 4.into())));
+        assert!(matches!(RatioField::<i32>::from_str_radix("10", 2), Err(FromStrErr::UnsupportedRadix)));
+        assert!(matches!(RatioField::<i32>::from_str_radix("99999999999", 10), Err(FromStrErr::Overflow)));
+        assert!(matches!(RatioField::<i32>::from_str_radix("x", 10), Err(FromStrErr::Parse(_))));
+        assert!(matches!(RatioField::<i32>::from_str_radix("+5", 10), Err(FromStrErr::Parse(ParseNumberError::Invalid))));
+        assert!(matches!(RatioField::<i32>::from_str_radix("1_0", 10), Err(FromStrErr::Parse(ParseNumberError::Invalid))));
+        assert!(matches!(RatioField::<i32>::from_str_radix("1/0", 10), Err(FromStrErr::Parse(ParseNumberError::ZeroDenominator))));
+    }
 
-impl From<ParseIntError> for FromStrErr {
-    fn from(err: ParseIntError) -> Self {
-        Self::ParseIntError(err)
+    #[test]
+    fn from_f64() {
+        assert_eq!(RatioField::<i32>::from_f64(-0.375), Some(field(-3, 8)));
+        assert_eq!(RatioField::<i32>::from_f64(0.1), None);
+        assert_eq!(RatioField::<i32>::from_f64(f64::NAN), None);
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,165 @@
+use num::{BigInt, BigRational, Zero, One, Integer};
+use config::fmt::{FmtAble, FmtEr};
+use crate::{polynomial::Polynomial, function::Expression, predict::Predict, error::{FitError, EvalError, require_unknowns, distinct, check}, linear_algebra::{Matrix, Vector}, util::{integer, as_points}};
+use alloc::{vec::Vec, string::String};
+
+// A function of the form p(x)/q(x), where p and q are polynomials
+#[derive(Debug)]
+pub struct RationalFunction {
+    numerator: Polynomial,
+    denominator: Polynomial,
+}
+impl RationalFunction {
+    pub fn from_values(values: &Vec<BigRational>, starting: i32) -> Result<Self, FitError> {
+        Self::from_points(&as_points(values, starting))
+    }
+    // Tries every combination of degrees, lowest total degree first. The denominator is
+    // always monic while solving, so that the system has a unique solution. If none of the
+    // degrees that can be checked fit, then more points are needed to check the next one.
+    pub fn from_points(points: &Vec<(BigRational, BigRational)>) -> Result<Self, FitError> {
+        require_unknowns(points, 2)?;
+        distinct(points)?;
+        for total in 1.. {
+            if points.len() <= total + 1 {
+                return Err(FitError::TooFewPoints(total + 2));
+            }
+            for denom_degree in 1..=total {
+                let numer_degree = total - denom_degree;
+                let mut matrix = Vec::with_capacity(total + 1);
+                let mut vector = Vec::with_capacity(total + 1);
+                // p(x) - y*(q(x) - x^n) = y*x^n
+                for point in points.iter().take(total + 1) {
+                    let mut row: Vec<BigRational> = (0..=numer_degree).map(|i| point.0.pow(i as i32)).collect();
+                    row.extend((0..denom_degree).map(|i| -&point.1 * point.0.pow(i as i32)));
+                    matrix.push(row);
+                    vector.push(&point.1 * point.0.pow(denom_degree as i32));
+                }
+                let mut numerator: Vec<BigRational> = match Matrix::from_rows(matrix).solve(&Vector::new(vector)) {
+                    Some(solution) => solution.into(),
+                    None => continue,
+                };
+                let mut denominator = numerator.split_off(numer_degree + 1);
+                denominator.push(BigRational::one());
+                let new = Self::new(numerator, denominator)?;
+                if check(points.iter().map(|point| new.apply(&point.0).as_ref() == Some(&point.1))).is_ok() {
+                    return Ok(new);
+                }
+            }
+        }
+        unreachable!()
+    }
+    // The coefficients are lowest degree first, and are scaled so that they are integers
+    // with no common factor
+    pub(crate) fn new(mut numerator: Vec<BigRational>, mut denominator: Vec<BigRational>) -> Result<Self, FitError> {
+        let lcm = numerator.iter().chain(denominator.iter()).fold(BigInt::one(), |lcm, c| lcm.lcm(c.denom()));
+        let gcd = numerator.iter().chain(denominator.iter()).fold(BigInt::zero(), |gcd, c| gcd.gcd(&(c * &lcm).to_integer()));
+        let scale = BigRational::new(lcm, gcd);
+        for c in numerator.iter_mut().chain(denominator.iter_mut()) {
+            *c *= &scale;
+        }
+        Ok(Self {
+            numerator: Polynomial::from_coefficients(&numerator)?,
+            denominator: Polynomial::from_coefficients(&denominator)?,
+        })
+    }
+    // The ordinary generating function a(0)+a(1)x+a(2)x^2+... of a sequence whose generating
+    // function times the denominator is a polynomial of lower degree. This is true if the
+    // denominator is 1-c1*x-...-cn*x^n and a(x)=c1*a(x-1)+...+cn*a(x-n) for x >= n, so only
+    // the first n values are needed.
+    pub(crate) fn generating_function(denominator: Vec<BigRational>, values: &[BigRational]) -> Result<Self, FitError> {
+        let numerator = (0..denominator.len() - 1)
+            .map(|k| (0..=k).fold(BigRational::zero(), |sum, i| sum + &denominator[i] * &values[k - i]))
+            .collect();
+        Self::new(numerator, denominator)
+    }
+    // Returns None if x is a pole
+    fn apply(&self, x: &BigRational) -> Option<BigRational> {
+        let denominator = self.denominator.eval(x);
+        if denominator.is_zero() {
+            None
+        }
+        else {
+            Some(self.numerator.eval(x) / denominator)
+        }
+    }
+    // The rational values of x for which the denominator is 0. Irrational poles cannot be
+    // found, and None is returned if the coefficients are too large to be sure of the rest.
+    pub fn poles(&self) -> Option<Vec<BigRational>> {
+        self.denominator.rational_roots()
+    }
+}
+// Multiplies the polynomial with the given coefficients (lowest degree first) by 1-x
+pub(crate) fn times_one_minus_x(coefficients: &mut Vec<BigRational>) {
+    coefficients.push(BigRational::zero());
+    for i in (1..coefficients.len()).rev() {
+        coefficients[i] = &coefficients[i] - &coefficients[i - 1];
+    }
+}
+impl From<&RationalFunction> for Expression<BigRational> {
+    fn from(rational: &RationalFunction) -> Self {
+        Expression::from(&rational.numerator) / Expression::from(&rational.denominator)
+    }
+}
+impl Predict for RationalFunction {
+    fn predict(&self, x: i32) -> Result<BigRational, EvalError> {
+        self.apply(&integer(x)).ok_or(EvalError::DivideByZero)
+    }
+}
+impl FmtAble for RationalFunction {
+    fn format(&self, f: &impl FmtEr) -> String {
+        f.fraction(self.numerator.format(f).as_str(), self.denominator.format(f).as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RationalFunction;
+    use crate::{error::FitError, util::{as_ratios, ratio}};
+    use config::fmt::{formatters, FmtAble};
+    const ASCII: formatters::ASCII = formatters::ASCII;
+
+    fn successor_ratio() -> RationalFunction {
+        RationalFunction::from_values(&vec![ratio(1, 2), ratio(2, 3), ratio(3, 4), ratio(4, 5)], 0).unwrap()
+    }
+
+    #[test]
+    fn reciprocal() {
+        assert_eq!(RationalFunction::from_values(&vec![ratio(1, 1), ratio(1, 2), ratio(1, 3), ratio(1, 4)], 1).unwrap().format(&ASCII), "1/x");
+    }
+
+    #[test]
+    fn linear_over_linear() {
+        assert_eq!(successor_ratio().format(&ASCII), "(x+1)/(x+2)");
+    }
+
+    #[test]
+    fn integer_coefficients() {
+        assert_eq!(RationalFunction::from_values(&vec![ratio(1, 1), ratio(1, 3), ratio(1, 5), ratio(1, 7)], 0).unwrap().format(&ASCII), "1/(2x+1)");
+    }
+
+    #[test]
+    fn poles() {
+        assert_eq!(successor_ratio().poles(), Some(vec![ratio(-2, 1)]));
+    }
+
+    #[test]
+    fn generating_function() {
+        // 1+2x+3x^2+... = 1/(1-x)^2
+        let generating_function = RationalFunction::generating_function(as_ratios(vec![1, -2, 1]), &as_ratios(vec![1, 2])).unwrap();
+        assert_eq!(generating_function.format(&ASCII), "1/(x^2-2x+1)");
+        assert_eq!(RationalFunction::generating_function(vec![ratio(1, 1), ratio(-1, 2)], &as_ratios(vec![3])).unwrap().format(&ASCII), "6/(-x+2)");
+    }
+
+    // (x+1)/(x+2) has 3 unknowns, so 3 values could fit it by chance
+    #[test]
+    fn too_few_values() {
+        let values = vec![ratio(1, 2), ratio(2, 3), ratio(3, 4)];
+        assert_eq!(RationalFunction::from_values(&values, 0).unwrap_err(), FitError::TooFewPoints(4));
+        assert_eq!(RationalFunction::from_values(&vec![ratio(1, 1), ratio(1, 2)], 1).unwrap_err(), FitError::TooFewPoints(3));
+    }
+
+    #[test]
+    fn polynomial() {
+        assert!(RationalFunction::from_values(&as_ratios(vec![0, 1, 4, 9, 16]), 0).is_err());
+    }
+}
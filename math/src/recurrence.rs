@@ -0,0 +1,184 @@
+use core::iter;
+use num::{BigRational, Zero, One};
+use config::fmt::{FmtAble, FmtEr};
+use crate::{polynomial::Polynomial, rational_fn::{RationalFunction, times_one_minus_x}, predict::Predict, error::{FitError, EvalError, require, check}, linear_algebra::{Matrix, Vector}, util::{format_ratio, format_term}};
+use alloc::{vec::Vec, string::{String, ToString}, boxed::Box};
+
+// A constant-coefficient linear recurrence, such as the fibonacci sequence
+// a(x) = a(x-1) + a(x-2), optionally with a constant term.
+#[derive(Debug)]
+pub struct Recurrence {
+    // The coefficient at index i is the coefficient of a(x-i-1)
+    coefficients: Vec<BigRational>,
+    constant: BigRational,
+    initial: Vec<BigRational>,
+}
+impl Recurrence {
+    pub fn from_values(values: &Vec<BigRational>) -> Result<Self, FitError> {
+        Self::find(values, &[false, true])
+    }
+    // Only finds recurrences without a constant term
+    pub(crate) fn homogeneous(values: &Vec<BigRational>) -> Result<Self, FitError> {
+        Self::find(values, &[false])
+    }
+    fn find(values: &Vec<BigRational>, constant_options: &[bool]) -> Result<Self, FitError> {
+        require(values, 3)?;
+        let mut error = FitError::Singular;
+        for order in 1..values.len() {
+            for &has_constant in constant_options.iter() {
+                let unknowns = order + has_constant as usize;
+                // There must be at least one more value than is needed to find the coefficients,
+                // otherwise any sequence would match
+                if values.len() - order <= unknowns {
+                    return Err(error);
+                }
+                let mut matrix = Vec::with_capacity(unknowns);
+                let mut vector = Vec::with_capacity(unknowns);
+                for i in order..order + unknowns {
+                    let mut row: Vec<BigRational> = values[i - order..i].iter().rev().cloned().collect();
+                    if has_constant {
+                        row.push(num::one());
+                    }
+                    matrix.push(row);
+                    vector.push(values[i].clone());
+                }
+                let mut coefficients: Vec<BigRational> = match Matrix::from_rows(matrix).solve(&Vector::new(vector)) {
+                    Some(solution) => solution.into(),
+                    None => continue,
+                };
+                let constant = if has_constant { coefficients.pop().unwrap() } else { BigRational::zero() };
+                let new = Self {
+                    coefficients,
+                    constant,
+                    initial: values[0..order].to_vec(),
+                };
+                // The first order() values are the initial values, so they always fit
+                match check((0..values.len()).map(|i| i < order || new.apply(&values[i - order..i]) == values[i])) {
+                    Ok(()) => return Ok(new),
+                    Err(e) => error = error.closest(e),
+                }
+            }
+        }
+        Err(error)
+    }
+    pub fn order(&self) -> usize {
+        self.coefficients.len()
+    }
+    // The polynomial t^n - c1*t^(n-1) - ... - cn, whose roots are the bases of the
+    // exponential terms in the closed form of a homogeneous recurrence
+    pub(crate) fn characteristic(&self) -> Result<Polynomial, FitError> {
+        let mut coefficients: Vec<BigRational> = self.coefficients.iter().rev().map(|c| -c).collect();
+        coefficients.push(BigRational::one());
+        Polynomial::from_coefficients(&coefficients)
+    }
+    // The ordinary generating function a(0)+a(1)x+a(2)x^2+..., whose denominator is
+    // 1-c1*x-...-cn*x^n, times 1-x if there is a constant term, or Overflow if that degree
+    // would be too large to store
+    pub fn generating_function(&self) -> Result<RationalFunction, FitError> {
+        let mut denominator = vec![BigRational::one()];
+        denominator.extend(self.coefficients.iter().map(|c| -c));
+        if !self.constant.is_zero() {
+            times_one_minus_x(&mut denominator);
+        }
+        let values: Vec<BigRational> = self.terms().take(denominator.len() - 1).collect();
+        RationalFunction::generating_function(denominator, &values)
+    }
+    // a(0), a(1), a(2), ...
+    fn terms(&self) -> impl Iterator<Item = BigRational> + '_ {
+        let mut previous: Vec<BigRational> = Vec::with_capacity(self.order());
+        (0..).map(move |i| {
+            let value = if i < self.order() { self.initial[i].clone() } else { self.apply(&previous) };
+            if i >= self.order() {
+                previous.remove(0);
+            }
+            previous.push(value.clone());
+            value
+        })
+    }
+    // previous must contain the last order() values, oldest first
+    fn apply(&self, previous: &[BigRational]) -> BigRational {
+        self.coefficients.iter().zip(previous.iter().rev())
+            .fold(self.constant.clone(), |sum, e| sum + e.0 * e.1)
+    }
+}
+impl Predict for Recurrence {
+    fn predict(&self, x: i32) -> Result<BigRational, EvalError> {
+        if x < 0 {
+            return Err(EvalError::Undefined);
+        }
+        Ok(self.terms().nth(x as usize).unwrap())
+    }
+
+    // Each value is found from the ones before it, so they are only found once
+    fn predictions(&self, from: i32) -> Box<dyn Iterator<Item = BigRational> + '_> {
+        if from < 0 {
+            return Box::new(iter::empty());
+        }
+        Box::new(self.terms().skip(from as usize))
+    }
+}
+impl FmtAble for Recurrence {
+    fn format(&self, f: &impl FmtEr) -> String {
+        let x = f.x().to_string();
+        let mut s = String::new();
+        for coefficient in self.coefficients.iter().enumerate() {
+            let term = format_term(coefficient.1, f.sequence_term(f.subtract(x.as_str(), (coefficient.0 + 1).to_string().as_str()).as_str()).as_str(), f);
+            if s.is_empty() { s = term }
+            else if !term.is_empty() { s = f.add(s.as_str(), term.as_str()) }
+        }
+        if s.is_empty() { s = format_ratio(&self.constant, f) }
+        else if !self.constant.is_zero() { s = f.add(s.as_str(), format_ratio(&self.constant, f).as_str()) }
+        s = format!("{}={}", f.sequence_term(x.as_str()), s);
+        for value in self.initial.iter().enumerate() {
+            s = format!("{}, {}={}", s, f.sequence_term(value.0.to_string().as_str()), format_ratio(value.1, f));
+        }
+        s
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Recurrence;
+    use crate::error::FitError;
+    use crate::util::as_ratios;
+    use config::fmt::{formatters, FmtAble};
+    const ASCII: formatters::ASCII = formatters::ASCII;
+
+    #[test]
+    fn fibonacci() {
+        assert_eq!(Recurrence::from_values(&as_ratios(vec![1, 1, 2, 3, 5, 8])).unwrap().format(&ASCII), "a(x)=a(x-1)+a(x-2), a(0)=1, a(1)=1");
+    }
+
+    #[test]
+    fn constant() {
+        assert_eq!(Recurrence::from_values(&as_ratios(vec![1, 1, 3, 5, 9, 15, 25])).unwrap().format(&ASCII), "a(x)=a(x-1)+a(x-2)+1, a(0)=1, a(1)=1");
+    }
+
+    #[test]
+    fn skipped_term() {
+        assert_eq!(Recurrence::from_values(&as_ratios(vec![1, 2, -1, -2, 1, 2])).unwrap().format(&ASCII), "a(x)=-a(x-2), a(0)=1, a(1)=2");
+    }
+
+    #[test]
+    fn order() {
+        assert_eq!(Recurrence::from_values(&as_ratios(vec![0, 0, 1, 1, 2, 4, 7, 13, 24])).unwrap().order(), 3);
+    }
+
+    #[test]
+    fn generating_function() {
+        assert_eq!(Recurrence::from_values(&as_ratios(vec![1, 1, 2, 3, 5, 8])).unwrap().generating_function().unwrap().format(&ASCII), "1/(-x^2-x+1)");
+        assert_eq!(Recurrence::from_values(&as_ratios(vec![1, 1, 3, 5, 9, 15, 25])).unwrap().generating_function().unwrap().format(&ASCII), "(x^2-x+1)/(x^3-2x+1)");
+        assert_eq!(Recurrence::from_values(&as_ratios(vec![1, 2, -1, -2, 1, 2])).unwrap().generating_function().unwrap().format(&formatters::Unicode), "(2𝑥+1)/(𝑥²+1)");
+    }
+
+    #[test]
+    fn too_short() {
+        assert_eq!(Recurrence::from_values(&as_ratios(vec![1, 1])).unwrap_err(), FitError::TooFewPoints(3));
+        assert!(Recurrence::from_values(&as_ratios(vec![1, 1, 2])).is_err());
+    }
+
+    #[test]
+    fn latex() {
+        assert_eq!(Recurrence::from_values(&as_ratios(vec![1, 1, 2, 3, 5, 8])).unwrap().format(&formatters::LaTeX), "a_{x}=a_{x-1}+a_{x-2}, a_{0}=1, a_{1}=1");
+    }
+}
@@ -0,0 +1,376 @@
+use core::cmp::Ordering;
+use num::{BigRational, Zero, One, Signed, ToPrimitive};
+use crate::function::{Expression::{self, *}, power};
+use alloc::{boxed::Box, vec::Vec};
+
+// The largest power of a sum that is multiplied out
+const MAX_EXPANSION: u32 = 8;
+
+impl Expression<BigRational> {
+    // Rewrites the expression as a sum of terms, where each term is a rational coefficient
+    // times a product of powers. Constants are evaluated when they are rational, like terms
+    // and like factors are combined, and the terms and factors are put in a canonical
+    // order, so equivalent expressions usually simplify to the same tree. Products of
+    // sums are only multiplied out if neither has negative or fractional powers, so
+    // x/(x+1) stays as it is.
+    pub fn simplify(&self) -> Self {
+        Sum::from_expression(self).to_expression()
+    }
+}
+
+// A rational coefficient times a product of powers. The bases are in canonical order with
+// none repeated, and no exponent is 0.
+#[derive(Clone, Debug, PartialEq)]
+struct Term {
+    coefficient: BigRational,
+    factors: Vec<(Expression<BigRational>, Expression<BigRational>)>,
+}
+impl Term {
+    fn constant(coefficient: BigRational) -> Self {
+        Self { coefficient, factors: Vec::new() }
+    }
+    fn power(base: Expression<BigRational>, exponent: Expression<BigRational>) -> Self {
+        let mut term = Self::constant(BigRational::one());
+        term.multiply_factor(base, exponent);
+        term
+    }
+    fn multiply(mut self, other: Self) -> Self {
+        self.coefficient *= other.coefficient;
+        for (base, exponent) in other.factors {
+            self.multiply_factor(base, exponent);
+        }
+        self
+    }
+    // Multiplies by base^exponent, adding the exponents if there is already a factor
+    // with the same base
+    fn multiply_factor(&mut self, base: Expression<BigRational>, exponent: Expression<BigRational>) {
+        let exponent = match self.factors.iter().position(|factor| factor.0 == base) {
+            Some(i) => Sum::from_expression(&self.factors.remove(i).1).add(Sum::from_expression(&exponent)).to_expression(),
+            None => exponent,
+        };
+        if let Val(e) = &exponent {
+            if e.is_zero() {
+                return;
+            }
+            if let Val(b) = &base {
+                if let Ok(value) = power(b, e) {
+                    self.coefficient *= value;
+                    return;
+                }
+            }
+        }
+        let i = self.factors.binary_search_by(|factor| factor.0.cmp(&base)).unwrap_or_else(|i| i);
+        self.factors.insert(i, (base, exponent));
+    }
+    fn is_constant(&self) -> bool {
+        self.factors.is_empty()
+    }
+    // Whether every exponent is a positive integer
+    fn is_polynomial(&self) -> bool {
+        self.factors.iter().all(|factor| matches!(&factor.1, Val(e) if e.is_integer() && e.is_positive()))
+    }
+    // The power of x, if it is rational, which is used to put higher powers first
+    fn degree(&self) -> BigRational {
+        self.factors.iter()
+            .find_map(|factor| match factor {
+                (Independent, Val(e)) => Some(e.clone()),
+                _ => None,
+            })
+            .unwrap_or_else(BigRational::zero)
+    }
+    // Factors with negative exponents are written as a denominator
+    fn to_expression(&self, coefficient: &BigRational) -> Expression<BigRational> {
+        let mut numerator = Vec::new();
+        let mut denominator = Vec::new();
+        for (base, exponent) in self.factors.iter() {
+            match exponent {
+                Val(e) if e.is_negative() => denominator.push(power_expression(base, Val(-e))),
+                _ => numerator.push(power_expression(base, exponent.clone())),
+            }
+        }
+        let mut e = match product(numerator) {
+            Some(numerator) if coefficient.is_one() => numerator,
+            Some(numerator) => Mul(Box::new(Val(coefficient.clone())), Box::new(numerator)),
+            None => Val(coefficient.clone()),
+        };
+        if let Some(denominator) = product(denominator) {
+            e = Div(Box::new(e), Box::new(denominator));
+        }
+        e
+    }
+}
+
+// Constants go last, and otherwise higher powers of x go first
+fn term_order(a: &Term, b: &Term) -> Ordering {
+    a.is_constant().cmp(&b.is_constant())
+        .then_with(|| b.degree().cmp(&a.degree()))
+        .then_with(|| a.factors.cmp(&b.factors))
+}
+
+fn power_expression(base: &Expression<BigRational>, exponent: Expression<BigRational>) -> Expression<BigRational> {
+    match &exponent {
+        Val(e) if e.is_one() => base.clone(),
+        _ => Pow(Box::new(base.clone()), Box::new(exponent)),
+    }
+}
+
+fn product(factors: Vec<Expression<BigRational>>) -> Option<Expression<BigRational>> {
+    factors.into_iter().fold(None, |product, factor| Some(match product {
+        Some(product) => Mul(Box::new(product), Box::new(factor)),
+        None => factor,
+    }))
+}
+
+// A sum of terms, with like terms combined, no coefficients of 0, and the terms in
+// canonical order
+#[derive(Clone, Debug, PartialEq)]
+struct Sum {
+    terms: Vec<Term>,
+}
+impl Sum {
+    fn from_expression(e: &Expression<BigRational>) -> Self {
+        match e {
+            Add(a, b) => Self::from_expression(a).add(Self::from_expression(b)),
+            Sub(a, b) => Self::from_expression(a).add(Self::from_expression(b).scale(&-BigRational::one())),
+            Mul(a, b) => Self::from_expression(a).multiply(Self::from_expression(b)),
+            Div(a, b) => Self::from_expression(a).multiply(Self::from_expression(b).pow(Self::constant(-BigRational::one()))),
+            Pow(a, b) => Self::from_expression(a).pow(Self::from_expression(b)),
+            Ln(a) => Self::function(Ln, a),
+            Sin(a) => Self::function(Sin, a),
+            Cos(a) => Self::function(Cos, a),
+            // |(|u|)| = |u|
+            Abs(a) if matches!(a.as_ref(), Abs(_)) => Self::from_expression(a),
+            Abs(a) => Self::function(Abs, a),
+            Floor(a) => Self::function(Floor, a),
+            Factorial(a) => Self::function(Factorial, a),
+            Val(v) => Self::constant(v.clone()),
+            Pi | Independent => Self::from_term(Term::power(e.clone(), Val(BigRational::one()))),
+        }
+    }
+    // A function of one argument is evaluated if the argument is a constant and the value
+    // is rational, such as ln(1) or sin(pi/6)
+    fn function(build: fn(Box<Expression<BigRational>>) -> Expression<BigRational>, argument: &Expression<BigRational>) -> Self {
+        Self::from_term(Term::power(build(Box::new(argument.simplify())), Val(BigRational::one())))
+    }
+    fn constant(c: BigRational) -> Self {
+        Self::from_term(Term::constant(c))
+    }
+    fn from_term(term: Term) -> Self {
+        if term.coefficient.is_zero() {
+            return Self { terms: Vec::new() };
+        }
+        // A product of constants, like ln(8)/ln(2), might still be rational
+        if !term.is_constant() && !term.factors.iter().any(|factor| factor.0.contains(&Independent) || factor.1.contains(&Independent)) {
+            if let Ok(value) = term.to_expression(&term.coefficient).eval(&BigRational::zero()) {
+                return Self::constant(value);
+            }
+        }
+        Self { terms: vec![term] }
+    }
+    // The value, if there are no terms with factors
+    fn as_constant(&self) -> Option<BigRational> {
+        match self.terms.as_slice() {
+            [] => Some(BigRational::zero()),
+            [term] if term.is_constant() => Some(term.coefficient.clone()),
+            _ => None,
+        }
+    }
+    fn constant_term(&self) -> BigRational {
+        self.terms.iter().find(|term| term.is_constant()).map_or_else(BigRational::zero, |term| term.coefficient.clone())
+    }
+    fn is_polynomial(&self) -> bool {
+        self.terms.iter().all(Term::is_polynomial)
+    }
+    // A sum with more than one term is treated as a single factor
+    fn into_term(self) -> Term {
+        match self.terms.len() {
+            0 => Term::constant(BigRational::zero()),
+            1 => self.terms.into_iter().next().unwrap(),
+            _ => Term::power(self.to_expression(), Val(BigRational::one())),
+        }
+    }
+    fn add(mut self, other: Self) -> Self {
+        for term in other.terms {
+            match self.terms.iter_mut().find(|like| like.factors == term.factors) {
+                Some(like) => like.coefficient += term.coefficient,
+                None => self.terms.push(term),
+            }
+        }
+        self.terms.retain(|term| !term.coefficient.is_zero());
+        self.terms.sort_by(term_order);
+        self
+    }
+    fn scale(mut self, c: &BigRational) -> Self {
+        if c.is_zero() {
+            return Self { terms: Vec::new() };
+        }
+        for term in self.terms.iter_mut() {
+            term.coefficient *= c;
+        }
+        self
+    }
+    fn multiply(self, other: Self) -> Self {
+        if let Some(c) = self.as_constant() {
+            return other.scale(&c);
+        }
+        if let Some(c) = other.as_constant() {
+            return self.scale(&c);
+        }
+        if self.terms.len() == 1 && other.terms.len() == 1 || self.is_polynomial() && other.is_polynomial() {
+            let mut product = Self { terms: Vec::new() };
+            for a in self.terms.iter() {
+                for b in other.terms.iter() {
+                    product = product.add(Self::from_term(a.clone().multiply(b.clone())));
+                }
+            }
+            product
+        }
+        else {
+            Self::from_term(self.into_term().multiply(other.into_term()))
+        }
+    }
+    fn pow(self, exponent: Self) -> Self {
+        let e = match exponent.as_constant() {
+            Some(e) => e,
+            None => return self.pow_expression(exponent),
+        };
+        if e.is_zero() {
+            return Self::constant(BigRational::one());
+        }
+        if e.is_one() {
+            return self;
+        }
+        if let Some(base) = self.as_constant() {
+            return Self::from_term(Term::power(Val(base), Val(e)));
+        }
+        if e.is_integer() {
+            // (c*a^p*b^q)^n = c^n*a^(pn)*b^(qn), but only if n is an integer
+            if self.terms.len() == 1 {
+                let term = &self.terms[0];
+                if let Ok(coefficient) = power(&term.coefficient, &e) {
+                    let mut result = Term::constant(coefficient);
+                    for (base, exponent) in term.factors.iter() {
+                        result.multiply_factor(base.clone(), Self::from_expression(exponent).scale(&e).to_expression());
+                    }
+                    return Self::from_term(result);
+                }
+            }
+            else if self.is_polynomial() {
+                if let Some(n) = e.to_integer().to_u32().filter(|n| *n <= MAX_EXPANSION) {
+                    return (1..n).fold(self.clone(), |product, _| product.multiply(self.clone()));
+                }
+            }
+        }
+        Self::from_term(Term::power(self.to_expression(), Val(e)))
+    }
+    // A power with x in the exponent. For a positive constant base, b^(u+c) = b^c*b^u,
+    // so the constant part of the exponent is taken out if b^c is rational.
+    fn pow_expression(self, exponent: Self) -> Self {
+        if let Some(base) = self.as_constant() {
+            if base.is_one() {
+                return Self::constant(base);
+            }
+            let c = exponent.constant_term();
+            if base.is_positive() && !c.is_zero() {
+                if let Ok(value) = power(&base, &c) {
+                    let mut term = Term::constant(value);
+                    term.multiply_factor(Val(base), exponent.add(Self::constant(-c)).to_expression());
+                    return Self::from_term(term);
+                }
+            }
+        }
+        Self::from_term(Term::power(self.to_expression(), exponent.to_expression()))
+    }
+    // Negative terms after the first are subtracted
+    fn to_expression(&self) -> Expression<BigRational> {
+        let mut terms = self.terms.iter();
+        let mut e = match terms.next() {
+            Some(first) => first.to_expression(&first.coefficient),
+            None => return Val(BigRational::zero()),
+        };
+        for term in terms {
+            let unsigned = term.to_expression(&term.coefficient.abs());
+            e = if term.coefficient.is_negative() { Sub(Box::new(e), Box::new(unsigned)) }
+                else { Add(Box::new(e), Box::new(unsigned)) };
+        }
+        e
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{function::{Function, Expression::{self, *}}, util::{integer, ratio}};
+    use num::BigRational;
+
+    fn val(n: i32) -> Expression<BigRational> {
+        Val(integer(n))
+    }
+
+    fn x() -> Expression<BigRational> {
+        Independent
+    }
+
+    #[test]
+    fn identities() {
+        assert_eq!((x() + val(0)).simplify(), x());
+        assert_eq!((x() * val(1)).simplify(), x());
+        assert_eq!(x().pow(val(1)).simplify(), x());
+        assert_eq!(x().pow(val(0)).simplify(), val(1));
+        assert_eq!((val(0) * x().ln()).simplify(), val(0));
+        assert_eq!(x().abs().abs().simplify(), x().abs());
+        assert_eq!((x() * integer(3)).simplify(), val(3) * x());
+    }
+
+    #[test]
+    fn constants() {
+        assert_eq!(((val(2) + val(3)) * x()).simplify(), val(5) * x());
+        assert_eq!(val(4).pow(Val(ratio(1, 2))).simplify(), val(2));
+        assert_eq!(val(1).ln().simplify(), val(0));
+        assert_eq!((Pi / val(6)).sin().simplify(), Val(ratio(1, 2)));
+        assert_eq!((val(8).ln() / val(2).ln()).simplify(), val(3));
+    }
+
+    #[test]
+    fn like_terms() {
+        assert_eq!((x() + x()).simplify(), val(2) * x());
+        assert_eq!((val(2) * x() - x()).simplify(), x());
+        assert_eq!((x() * x()).simplify(), x().pow(val(2)));
+        assert_eq!((x().pow(val(3)) / x()).simplify(), x().pow(val(2)));
+        assert_eq!((x() - x()).simplify(), val(0));
+    }
+
+    #[test]
+    fn powers() {
+        assert_eq!(x().pow(val(2)).pow(val(3)).simplify(), x().pow(val(6)));
+        assert_eq!((val(2) * x()).pow(val(2)).simplify(), val(4) * x().pow(val(2)));
+        let sqrt2 = val(2).pow(Val(ratio(1, 2)));
+        assert_eq!((sqrt2.clone() * sqrt2.clone()).simplify(), val(2));
+        assert_eq!(val(2).pow(x() + val(1)).simplify(), val(2) * val(2).pow(x()));
+        // sqrt(x^2) is |x|, not x
+        assert_eq!(x().pow(val(2)).pow(Val(ratio(1, 2))).simplify(), x().pow(val(2)).pow(Val(ratio(1, 2))));
+    }
+
+    #[test]
+    fn canonical_order() {
+        let expected = x().pow(val(2)) + x() + val(1);
+        assert_eq!((val(1) + x() + x().pow(val(2))).simplify(), expected);
+        assert_eq!((x() + val(1)).pow(val(2)).simplify(), x().pow(val(2)) + val(2) * x() + val(1));
+        assert_eq!((val(3) - x()).simplify(), Val(integer(-1)) * x() + val(3));
+        assert_eq!((x() * Pi).simplify(), Pi * x());
+    }
+
+    #[test]
+    fn symbolic() {
+        // log_2(x) keeps ln(2), and a rational function isn't multiplied out
+        let log = x().ln() / val(2).ln();
+        assert_eq!(log.simplify(), log);
+        let rational = (x() + val(1)) / (x() + val(2));
+        assert_eq!(rational.simplify(), rational);
+    }
+
+    #[test]
+    fn equivalent_functions() {
+        assert_eq!(Function::from(x() * (x() + val(1))), Function::from(x().pow(val(2)) + x()));
+        assert_ne!(Function::from(x() * (x() + val(1))), Function::from(x().pow(val(2))));
+    }
+}
@@ -0,0 +1,169 @@
+use num::{BigRational, Zero, rational::Ratio};
+use config::fmt::{FmtAble, FmtEr};
+use crate::{function::Expression, predict::Predict, error::{FitError, EvalError, require, check}, util::{integer, format_ratio, format_term}};
+use alloc::{vec::Vec, string::{String, ToString}};
+
+// a*sin(b*x+c)+d, where b and c are stored as rational multiples of pi. They are always
+// multiples of pi/6 between 0 and 2pi, so they don't need arbitrary precision.
+#[derive(Debug)]
+pub struct Sinusoidal {
+    amplitude: BigRational,
+    frequency: Ratio<i32>,
+    phase: Ratio<i32>,
+    midline: BigRational,
+}
+impl Sinusoidal {
+    pub fn from_values(values: &Vec<BigRational>) -> Result<Self, FitError> {
+        require(values, 4)?;
+        let mut error = FitError::Singular;
+        // By Niven's theorem, sin(b*x+c) can only be rational for every integer x if b
+        // is a multiple of pi/3 or pi/2, so these are the only frequencies checked
+        // (shortest period first). Both b and c are in sixths of pi.
+        for &frequency in [6, 4, 3, 2].iter() {
+            let period = 12 / num::integer::gcd(frequency, 12);
+            if values.len() < period as usize {
+                continue;
+            }
+            // The phases pi to 2pi are the same as 0 to pi with a negative amplitude,
+            // and sin (0) or cos (pi/2) are tried before any other phase
+            for &phase in [0, 3, 1, 2, 4, 5].iter() {
+                let basis: Option<Vec<BigRational>> = (0..values.len() as i32)
+                    .map(|x| sin_pi(Ratio::new(frequency * x + phase, 6)))
+                    .collect();
+                let basis = match basis {
+                    Some(basis) => basis,
+                    None => continue,
+                };
+                let other = match basis.iter().position(|e| *e != basis[0]) {
+                    Some(i) => i,
+                    None => continue,
+                };
+                let amplitude = (&values[other] - &values[0]) / (&basis[other] - &basis[0]);
+                if amplitude.is_zero() {
+                    continue;
+                }
+                let new = Self {
+                    midline: &values[0] - &amplitude * &basis[0],
+                    amplitude,
+                    frequency: Ratio::new(frequency, 6),
+                    phase: Ratio::new(phase, 6),
+                };
+                match check(basis.iter().zip(values.iter()).map(|e| &new.amplitude * e.0 + &new.midline == *e.1)) {
+                    Ok(()) => return Ok(new),
+                    Err(e) => error = error.closest(e),
+                }
+            }
+        }
+        Err(error)
+    }
+    // Moves the graph dx to the right
+    pub(crate) fn shift(&mut self, dx: i32) {
+        let mut phase = self.phase - self.frequency * dx;
+        phase = phase - (phase / 2).floor() * 2;
+        if phase >= Ratio::from_integer(1) {
+            phase -= 1;
+            self.amplitude = -&self.amplitude;
+        }
+        self.phase = phase;
+    }
+}
+impl From<&Sinusoidal> for Expression<BigRational> {
+    fn from(sinusoidal: &Sinusoidal) -> Self {
+        let to_big = |r: &Ratio<i32>| BigRational::new((*r.numer()).into(), (*r.denom()).into());
+        let mut argument = (Expression::Pi * Expression::Independent).times(&to_big(&sinusoidal.frequency));
+        if !sinusoidal.phase.is_zero() {
+            argument = argument + Expression::Pi.times(&to_big(&sinusoidal.phase));
+        }
+        argument.sin().times(&sinusoidal.amplitude).plus(&sinusoidal.midline)
+    }
+}
+impl Predict for Sinusoidal {
+    fn predict(&self, x: i32) -> Result<BigRational, EvalError> {
+        Expression::from(self).eval(&integer(x))
+    }
+}
+impl FmtAble for Sinusoidal {
+    fn format(&self, f: &impl FmtEr) -> String {
+        let pi = f.pi();
+        let mut arg = f.divide(
+            format_term(&integer(*self.frequency.numer()), f.multiply(pi.as_str(), f.x().to_string().as_str()).as_str(), f).as_str(),
+            self.frequency.denom().to_string().as_str());
+        let trig;
+        if self.phase == Ratio::new(1, 2) {
+            trig = f.cos(arg.as_str());
+        }
+        else {
+            if !self.phase.is_zero() {
+                arg = f.add(arg.as_str(), f.divide(
+                    format_term(&integer(*self.phase.numer()), pi.as_str(), f).as_str(),
+                    self.phase.denom().to_string().as_str()).as_str());
+            }
+            trig = f.sin(arg.as_str());
+        }
+        let mut s = format_term(&self.amplitude, trig.as_str(), f);
+        if !self.midline.is_zero() {
+            s = f.add(s.as_str(), format_ratio(&self.midline, f).as_str());
+        }
+        s
+    }
+}
+
+// Returns sin(r*pi) if it is rational
+pub(crate) fn sin_pi(r: Ratio<i32>) -> Option<BigRational> {
+    let sixths = r * 6;
+    if !sixths.is_integer() {
+        return None;
+    }
+    let half = BigRational::new(1.into(), 2.into());
+    match sixths.to_integer().rem_euclid(12) {
+        0 | 6 => Some(BigRational::zero()),
+        1 | 5 => Some(half),
+        3 => Some(integer(1)),
+        7 | 11 => Some(-half),
+        9 => Some(integer(-1)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Sinusoidal;
+    use crate::util::as_ratios;
+    use config::fmt::{formatters, FmtAble};
+    const ASCII: formatters::ASCII = formatters::ASCII;
+
+    #[test]
+    fn sin() {
+        assert_eq!(Sinusoidal::from_values(&as_ratios(vec![0, 1, 0, -1])).unwrap().format(&ASCII), "sin(pi*x/2)");
+    }
+
+    #[test]
+    fn cos() {
+        assert_eq!(Sinusoidal::from_values(&as_ratios(vec![1, 0, -1, 0])).unwrap().format(&ASCII), "cos(pi*x/2)");
+    }
+
+    #[test]
+    fn alternating() {
+        assert_eq!(Sinusoidal::from_values(&as_ratios(vec![1, -1, 1, -1])).unwrap().format(&ASCII), "cos(pi*x)");
+    }
+
+    #[test]
+    fn stretched_and_translated() {
+        assert_eq!(Sinusoidal::from_values(&as_ratios(vec![3, 5, 3, 1, 3])).unwrap().format(&ASCII), "2*sin(pi*x/2)+3");
+    }
+
+    #[test]
+    fn phase_shift() {
+        assert_eq!(Sinusoidal::from_values(&as_ratios(vec![1, 2, 1, -1, -2, -1])).unwrap().format(&ASCII), "2*sin(pi*x/3+pi/6)");
+    }
+
+    #[test]
+    fn constant() {
+        assert!(Sinusoidal::from_values(&as_ratios(vec![2, 2, 2, 2])).is_err());
+    }
+
+    #[test]
+    fn not_periodic() {
+        assert!(Sinusoidal::from_values(&as_ratios(vec![1, 2, 3, 4])).is_err());
+    }
+}
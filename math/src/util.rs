@@ -1,6 +1,10 @@
-#[cfg(test)]
-use num::rational::Ratio;
-use alloc::vec::Vec;
+use num::{BigInt, BigRational, Zero, One, Signed};
+use config::fmt::FmtEr;
+use alloc::{vec::Vec, string::{String, ToString}};
+
+// Trial division only goes up to this, which keeps factoring fast for numbers with many
+// digits. Divisors larger than this are only found if their cofactor is smaller.
+pub const MAX_TRIAL_DIVISOR: u32 = 1_000_000;
 
 pub fn all_equal(vec: &Vec<impl PartialEq>) -> bool {
     let prev = &vec[0];
@@ -12,18 +16,87 @@ pub fn all_equal(vec: &Vec<impl PartialEq>) -> bool {
     true
 }
 
+// Returns the nth root of r if it is rational
+pub fn root(r: &BigRational, n: u32) -> Option<BigRational> {
+    if r.is_negative() && n % 2 == 0 {
+        return None;
+    }
+    let int_root = |i: &BigInt| Some(i.nth_root(n)).filter(|root| &root.pow(n) == i);
+    Some(BigRational::new(int_root(r.numer())?, int_root(r.denom())?))
+}
+
+// Converts an index, such as the position of a value, into a rational number
+pub fn integer(i: impl Into<BigInt>) -> BigRational {
+    BigRational::from_integer(i.into())
+}
+
+// Pairs each value with its x value, where the first value is at x = starting
+pub fn as_points<T: Clone>(values: &[T], starting: i32) -> Vec<(BigRational, T)> {
+    values.iter().enumerate().map(|e| (integer(BigInt::from(e.0) + starting), e.1.clone())).collect()
+}
+
+pub fn format_ratio(ratio: &BigRational, f: &impl FmtEr) -> String {
+    let s = f.divide(ratio.numer().abs().to_string().as_str(), ratio.denom().to_string().as_str());
+    if ratio.is_negative() {
+        f.neg(&s)
+    }
+    else {
+        s
+    }
+}
+
+// Formats a ratio so that it can be raised to a power, adding parentheses if it is
+// negative or a fraction
+pub fn format_base(base: &BigRational, f: &impl FmtEr) -> String {
+    let s = format_ratio(base, f);
+    if base.is_negative() || !base.is_integer() {
+        format!("({})", s)
+    }
+    else {
+        s
+    }
+}
+
+// Formats coefficient * term, leaving out the coefficient if it is 1 or -1. If term
+// is empty, then only the coefficient is formatted.
+pub fn format_term(coefficient: &BigRational, term: &str, f: &impl FmtEr) -> String {
+    let mut s = String::new();
+    if !coefficient.is_zero() {
+        let coeff_abs = coefficient.abs();
+        if !coeff_abs.is_one() || term.is_empty() {
+            let mut coeff_str = f.divide(coeff_abs.numer().to_string().as_str(), coeff_abs.denom().to_string().as_str());
+            if !coefficient.denom().is_one() {
+                coeff_str = format!("({})", coeff_str);
+            }
+            s += coeff_str.as_str();
+        }
+        if s.is_empty() { s = term.to_string() }
+        else if !term.is_empty() { s = f.multiply(s.as_str(), term) }
+        if coefficient.is_negative() {
+            s = f.neg(&s);
+        }
+    }
+    s
+}
+
 #[cfg(test)]
-pub fn as_ratios(vec: Vec<i32>) -> Vec<Ratio<i32>> { // Not a test, just used by tests
+pub fn as_ratios(vec: Vec<i32>) -> Vec<BigRational> { // Not a test, just used by tests
     let mut new = Vec::new();
     for int in vec.iter() {
-        new.push(Ratio::from_integer(*int));
+        new.push(ratio(*int, 1));
     }
     new
 }
 
+#[cfg(test)]
+pub fn ratio(numer: i32, denom: i32) -> BigRational { // Also just used by tests
+    BigRational::new(numer.into(), denom.into())
+}
+
 #[cfg(test)]
 mod test {
         use super::all_equal as vec_equality_checker;
+        use super::{root, ratio};
 
         #[test]
         fn all_equal() {
@@ -34,4 +107,11 @@ mod test {
         fn not_all_equal() {
             assert!(!vec_equality_checker(&vec![1, 2, 3]));
         }
-    }
\ No newline at end of file
+
+        #[test]
+        fn rational_root() {
+            assert_eq!(root(&ratio(-8, 27), 3), Some(ratio(-2, 3)));
+            assert_eq!(root(&ratio(2, 1), 2), None);
+            assert_eq!(root(&ratio(-4, 1), 2), None);
+        }
+    }
@@ -1,8 +1,38 @@
-use num::rational::Ratio;
+use std::fmt::{self, Display, Formatter};
+use num::{BigInt, BigRational, Zero, bigint::ParseBigIntError, pow::Pow};
 use regex::Regex;
-use std::{convert::TryInto, num::ParseIntError};
 
-pub fn parse(mut s: &str) -> Result<Ratio<i32>, ParseIntError> {
+// The reason that a string can't be read as a number
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseNumberError {
+    // The string isn't written in any of the forms that are understood
+    Invalid,
+    // A fraction has a denominator of 0
+    ZeroDenominator,
+    // A number is too large to work with, such as a radicand whose square factors can't be found
+    TooLarge,
+}
+impl Display for ParseNumberError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            ParseNumberError::Invalid => write!(f, "Not a valid number"),
+            ParseNumberError::ZeroDenominator => write!(f, "Division by zero"),
+            ParseNumberError::TooLarge => write!(f, "A number is too large"),
+        }
+    }
+}
+impl From<ParseBigIntError> for ParseNumberError {
+    fn from(_: ParseBigIntError) -> Self {
+        ParseNumberError::Invalid
+    }
+}
+
+// Returns an error instead of panicking if the denominator is 0
+fn nonzero(denominator: BigInt) -> Result<BigInt, ParseNumberError> {
+    if denominator.is_zero() { Err(ParseNumberError::ZeroDenominator) } else { Ok(denominator) }
+}
+
+pub fn parse(mut s: &str) -> Result<BigRational, ParseNumberError> {
     s = s.trim();
     if Regex::new(r"^-?\d*\.?\d+$").unwrap().is_match(s) {
         Ok(match s.find('.') {
@@ -15,15 +45,14 @@ pub fn parse(mut s: &str) -> Result<Ratio<i32>, ParseIntError> {
                     index -= 1;
                 }
                 let ratio = ( if index == 0 { num::zero() }
-                    else { Ratio::from_integer((&s[0..index]).parse()?) } )
-                + Ratio::new(
+                    else { BigRational::from_integer((&s[0..index]).parse()?) } )
+                + BigRational::new(
                     (&s[index + 1..s.len()]).parse()?, 
-                    10i32.pow((s.len() - index - 1).try_into().unwrap())
+                    BigInt::from(10).pow(s.len() - index - 1)
                 );
-                let neg = if negative { -1 } else { 1 };
-                ratio * neg
+                if negative { -ratio } else { ratio }
             },
-            None => Ratio::from_integer(s.parse()?),
+            None => BigRational::from_integer(s.parse()?),
         })
     }
     else if Regex::new(format!(r"^-?(?:\d+\s+)?\d+\s*/\s*-?\d+$").as_str()).unwrap().is_match(s) {
@@ -31,19 +60,61 @@ pub fn parse(mut s: &str) -> Result<Ratio<i32>, ParseIntError> {
         let mut matches: Vec<_> = number_regex.find_iter(s).collect();
         let whole;
         if matches.len() == 3 {
-            whole = Ratio::from_integer(matches.remove(0).as_str().parse()?);
+            whole = BigRational::from_integer(matches.remove(0).as_str().parse()?);
         }
         else {
             whole = num::zero();
         }
         assert_eq!(matches.len(), 2);
-        Ok(whole + Ratio::new(if s.starts_with('-') { -1 } else { 1 } * num::abs(matches[0].as_str().parse::<i32>()?), matches[1].as_str().parse()?))
+        let numer = num::abs(matches[0].as_str().parse::<BigInt>()?);
+        let denom = nonzero(matches[1].as_str().parse()?)?;
+        Ok(whole + BigRational::new(if s.starts_with('-') { -numer } else { numer }, denom))
     }
     else {
-        match s.parse::<u32>() {
-            Ok(_parsed) => panic!("Valid number not matched"),
-            Err(err) => Err(err),
-        }
+        // BigInt accepts some forms that aren't matched above, like +5 and 1_0
+        Err(ParseNumberError::Invalid)
+    }
+}
+
+// Parses a number of the form a+b*sqrt(d), such as 3+2sqrt(5), (1+√5)/2 or sqrt(2)/2. The
+// result is (a, b, d), which is (a, 0, 1) if the number is rational.
+pub fn parse_radical(mut s: &str) -> Result<(BigRational, BigRational, BigInt), ParseNumberError> {
+    s = s.trim();
+    if let Ok(ratio) = parse(s) {
+        return Ok((ratio, num::zero(), num::one()));
+    }
+    if let Some(captures) = Regex::new(r"^\((.+)\)\s*/\s*(\d+)$").unwrap().captures(s) {
+        let (rational, irrational, radicand) = parse_radical(&captures[1])?;
+        let denominator = BigRational::from_integer(nonzero(captures[2].parse()?)?);
+        return Ok((rational / &denominator, irrational / denominator, radicand));
+    }
+    let regex = Regex::new(concat!(
+        r"^(?:(?P<rational>-?[\d./ ]*\d)\s*(?P<sign>[+-]))?\s*(?P<negative>-)?\s*(?P<coefficient>[\d./ ]*\d)?\s*\*?\s*",
+        r"(?:sqrt\(\s*(?P<radicand>\d+)\s*\)|√(?P<unicode_radicand>\d+))(?:\s*/\s*(?P<denominator>\d+))?$"
+    )).unwrap();
+    match regex.captures(s) {
+        Some(captures) => {
+            let rational = match captures.name("rational") {
+                Some(rational) => parse(rational.as_str())?,
+                None => num::zero(),
+            };
+            let mut irrational = match captures.name("coefficient") {
+                Some(coefficient) => parse(coefficient.as_str())?,
+                None => num::one(),
+            };
+            if captures.name("sign").map(|sign| sign.as_str()) == Some("-") {
+                irrational = -irrational;
+            }
+            if captures.name("negative").is_some() {
+                irrational = -irrational;
+            }
+            if let Some(denominator) = captures.name("denominator") {
+                irrational = irrational / BigRational::from_integer(nonzero(denominator.as_str().parse()?)?);
+            }
+            let radicand = captures.name("radicand").or_else(|| captures.name("unicode_radicand")).unwrap().as_str().parse()?;
+            Ok((rational, irrational, radicand))
+        },
+        None => parse(s).map(|ratio| (ratio, num::zero(), num::one())),
     }
 }
 
@@ -51,53 +122,90 @@ pub fn parse(mut s: &str) -> Result<Ratio<i32>, ParseIntError> {
 mod tests {
     use super::*;
 
+    fn ratio(numer: i32, denom: i32) -> BigRational {
+        BigRational::new(numer.into(), denom.into())
+    }
+
     #[test]
     fn whole_number() {
-        assert_eq!(parse("2").unwrap(), Ratio::from_integer(2));
+        assert_eq!(parse("2").unwrap(), ratio(2, 1));
     }
 
     #[test]
     fn decimal() {
-        assert_eq!(parse("1.3").unwrap(), Ratio::new(13, 10));
+        assert_eq!(parse("1.3").unwrap(), ratio(13, 10));
     }
 
     #[test]
     fn fraction() {
-        assert_eq!(parse("1 / 2").unwrap(), Ratio::new(1, 2));
+        assert_eq!(parse("1 / 2").unwrap(), ratio(1, 2));
     }
 
     #[test]
     fn multi_digit_fraction_denom() {
-        assert_eq!(parse("1 / 16").unwrap(), Ratio::new(1, 16));
+        assert_eq!(parse("1 / 16").unwrap(), ratio(1, 16));
     }
 
     #[test]
     fn multi_digit_fraction_numer() {
-        assert_eq!(parse("16 / 3").unwrap(), Ratio::new(16, 3))
+        assert_eq!(parse("16 / 3").unwrap(), ratio(16, 3))
     }
 
     #[test]
     fn mixed_number() {
-        assert_eq!(parse("1 1/2").unwrap(), Ratio::new(3, 2));
+        assert_eq!(parse("1 1/2").unwrap(), ratio(3, 2));
     }
 
     #[test]
     fn negative() {
-        assert_eq!(parse("-2").unwrap(), Ratio::from_integer(-2));
+        assert_eq!(parse("-2").unwrap(), ratio(-2, 1));
     }
 
     #[test]
     fn negative_decimal() {
-        assert_eq!(parse("-1.3").unwrap(), Ratio::new(-13, 10));
+        assert_eq!(parse("-1.3").unwrap(), ratio(-13, 10));
     }
 
     #[test]
     fn negative_fraction() {
-        assert_eq!(parse("-1 / 2").unwrap(), Ratio::new(-1, 2));
+        assert_eq!(parse("-1 / 2").unwrap(), ratio(-1, 2));
     }
 
     #[test]
     fn negative_mixed_number() {
-        assert_eq!(parse("-1 1/2").unwrap(), Ratio::new(-3, 2));
+        assert_eq!(parse("-1 1/2").unwrap(), ratio(-3, 2));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn radical() {
+        assert_eq!(parse_radical("3+2sqrt(5)").unwrap(), (ratio(3, 1), ratio(2, 1), BigInt::from(5)));
+    }
+
+    #[test]
+    fn radical_spaces() {
+        assert_eq!(parse_radical("1 - 1/2 * sqrt(3)").unwrap(), (ratio(1, 1), ratio(-1, 2), BigInt::from(3)));
+    }
+
+    #[test]
+    fn negative_radical() {
+        assert_eq!(parse_radical("-sqrt(2)").unwrap(), (ratio(0, 1), ratio(-1, 1), BigInt::from(2)));
+    }
+
+    #[test]
+    fn radical_fraction() {
+        assert_eq!(parse_radical("(1+√5)/2").unwrap(), (ratio(1, 2), ratio(1, 2), BigInt::from(5)));
+        assert_eq!(parse_radical("sqrt(2)/2").unwrap(), (ratio(0, 1), ratio(1, 2), BigInt::from(2)));
+    }
+
+    #[test]
+    fn rational_radical() {
+        assert_eq!(parse_radical("1/2").unwrap(), (ratio(1, 2), ratio(0, 1), BigInt::from(1)));
+    }
+
+    #[test]
+    fn invalid_radical() {
+        assert!(parse_radical("sqrt(x)").is_err());
+        assert_eq!(parse_radical("sqrt(2)/0"), Err(ParseNumberError::ZeroDenominator));
+        assert_eq!(parse_radical("(1+sqrt(5))/0"), Err(ParseNumberError::ZeroDenominator));
+    }
+}